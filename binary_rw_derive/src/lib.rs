@@ -0,0 +1,314 @@
+//! Derive macros for the `Encode` and `Decode` traits of `binary_rw`.
+//!
+//! Use the macros through the `derive` feature of `binary_rw`, the
+//! layout attributes are documented on `binary_rw::Encode`.
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parenthesized, parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, Expr,
+    Fields, Generics, Index, Member, Result,
+};
+
+/// Derive `binary_rw::Encode` for a struct.
+#[proc_macro_derive(Encode, attributes(binary_rw))]
+pub fn derive_encode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_encode(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derive `binary_rw::Decode` for a struct.
+#[proc_macro_derive(Decode, attributes(binary_rw))]
+pub fn derive_decode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_decode(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Layout attributes of a struct.
+struct Container {
+    magic: Option<Expr>,
+}
+
+/// Layout attributes of a field.
+struct Field {
+    member: Member,
+    name: String,
+    magic: Option<Expr>,
+    pad_before: Option<Expr>,
+    count: Option<Expr>,
+    condition: Option<Expr>,
+    asserts: Vec<Expr>,
+}
+
+impl Container {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut container = Container { magic: None };
+        for attr in attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("binary_rw"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("magic") {
+                    container.magic = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported binary_rw attribute on a struct"))
+                }
+            })?;
+        }
+        Ok(container)
+    }
+}
+
+impl Field {
+    fn parse(index: usize, field: &syn::Field) -> Result<Self> {
+        let (member, name) = match &field.ident {
+            Some(ident) => (Member::Named(ident.clone()), ident.to_string()),
+            None => (Member::Unnamed(Index::from(index)), index.to_string()),
+        };
+        let mut parsed = Field {
+            member,
+            name,
+            magic: None,
+            pad_before: None,
+            count: None,
+            condition: None,
+            asserts: Vec::new(),
+        };
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("binary_rw"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("magic") {
+                    parsed.magic = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("pad_before") {
+                    parsed.pad_before = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("count") {
+                    parsed.count = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("if") {
+                    parsed.condition = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("assert") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    parsed.asserts.push(content.parse()?);
+                } else {
+                    return Err(meta.error("unsupported binary_rw attribute on a field"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(parsed)
+    }
+
+    /// Return early when an assertion does not hold.
+    fn check_asserts(&self) -> TokenStream2 {
+        let checks = self.asserts.iter().map(|assert| {
+            let text = quote!(#assert).to_string();
+            quote! {
+                if !(#assert) {
+                    return ::core::result::Result::Err(
+                        ::binary_rw::BinaryError::AssertionFailed(#text),
+                    );
+                }
+            }
+        });
+        quote!(#(#checks)*)
+    }
+
+    fn encode(&self) -> TokenStream2 {
+        let member = &self.member;
+        let pad = self
+            .pad_before
+            .as_ref()
+            .map(|pad| quote!(writer.write_bytes_with_value(#pad, 0)?;));
+        let magic = self
+            .magic
+            .as_ref()
+            .map(|magic| quote!(writer.write_magic_bytes(#magic)?;));
+        let asserts = self.check_asserts();
+        let value = match &self.count {
+            Some(count) => quote! {
+                let count: usize = #count;
+                if self.#member.len() != count {
+                    return ::core::result::Result::Err(
+                        ::binary_rw::BinaryError::CountMismatch {
+                            expected: count,
+                            actual: self.#member.len(),
+                        },
+                    );
+                }
+                for element in self.#member.iter() {
+                    ::binary_rw::Encode::encode(element, writer)?;
+                }
+            },
+            None => quote!(::binary_rw::Encode::encode(&self.#member, writer)?;),
+        };
+        let body = quote! {
+            #asserts
+            #pad
+            #magic
+            #value
+        };
+        match &self.condition {
+            Some(condition) => quote! {
+                if #condition {
+                    #body
+                }
+            },
+            None => body,
+        }
+    }
+
+    fn decode(&self) -> TokenStream2 {
+        let member = &self.member;
+        let name = &self.name;
+        let pad = self.pad_before.as_ref().map(|pad| {
+            // Skip the padding through a scratch buffer so it is
+            // not charged against the allocation limits
+            quote! {
+                {
+                    let mut remaining: usize = #pad;
+                    let mut scratch = [0u8; 64];
+                    while remaining > 0 {
+                        let length = remaining.min(scratch.len());
+                        reader.read_into_slice(&mut scratch[..length])?;
+                        remaining -= length;
+                    }
+                }
+            }
+        });
+        let magic = self
+            .magic
+            .as_ref()
+            .map(|magic| quote!(reader.expect_bytes(#magic)?;));
+        let asserts = self.check_asserts();
+        let value = match &self.count {
+            Some(count) => quote! {
+                let count: usize = #count;
+                reader.check_elements(count)?;
+                self.#member.clear();
+                for index in 0..count {
+                    let mut element = ::core::default::Default::default();
+                    reader.element(index, |reader| {
+                        ::binary_rw::Decode::decode(&mut element, reader)
+                    })?;
+                    self.#member.push(element);
+                }
+            },
+            None => quote!(::binary_rw::Decode::decode(&mut self.#member, reader)?;),
+        };
+        let body = quote! {
+            reader.field(#name, |reader| {
+                #pad
+                #magic
+                #value
+                #asserts
+                ::core::result::Result::Ok(())
+            })?;
+        };
+        match &self.condition {
+            Some(condition) => quote! {
+                if #condition {
+                    #body
+                } else {
+                    self.#member = ::core::default::Default::default();
+                }
+            },
+            None => body,
+        }
+    }
+}
+
+fn fields(input: &DeriveInput, derive: &str) -> Result<Vec<Field>> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                format!("{} can only be derived for structs", derive),
+            ))
+        }
+    };
+    match fields {
+        Fields::Named(fields) => fields.named.iter(),
+        Fields::Unnamed(fields) => fields.unnamed.iter(),
+        Fields::Unit => return Ok(Vec::new()),
+    }
+    .enumerate()
+    .map(|(index, field)| Field::parse(index, field))
+    .collect()
+}
+
+/// Add a bound to every type parameter so the generated impl only
+/// applies when the fields can be encoded or decoded.
+fn add_bounds(generics: &Generics, bound: TokenStream2) -> Generics {
+    let mut generics = generics.clone();
+    let params: Vec<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause.predicates.push(parse_quote!(#param: #bound));
+    }
+    generics
+}
+
+fn expand_encode(input: &DeriveInput) -> Result<TokenStream2> {
+    let container = Container::parse(&input.attrs)?;
+    let fields = fields(input, "Encode")?;
+    let magic = container
+        .magic
+        .as_ref()
+        .map(|magic| quote!(writer.write_magic_bytes(#magic)?;));
+    let encode = fields.iter().map(Field::encode);
+    let ident = &input.ident;
+    let generics = add_bounds(&input.generics, quote!(::binary_rw::Encode));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::binary_rw::Encode for #ident #ty_generics #where_clause {
+            fn encode(
+                &self,
+                writer: &mut ::binary_rw::BinaryWriter,
+            ) -> ::binary_rw::Result<()> {
+                #magic
+                #(#encode)*
+                ::core::result::Result::Ok(())
+            }
+        }
+    })
+}
+
+fn expand_decode(input: &DeriveInput) -> Result<TokenStream2> {
+    let container = Container::parse(&input.attrs)?;
+    let fields = fields(input, "Decode")?;
+    let magic = container
+        .magic
+        .as_ref()
+        .map(|magic| quote!(reader.expect_bytes(#magic)?;));
+    let decode = fields.iter().map(Field::decode);
+    let ident = &input.ident;
+    let generics = add_bounds(
+        &input.generics,
+        quote!(::binary_rw::Decode + ::core::default::Default),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::binary_rw::Decode for #ident #ty_generics #where_clause {
+            fn decode(
+                &mut self,
+                reader: &mut ::binary_rw::BinaryReader,
+            ) -> ::binary_rw::Result<()> {
+                #magic
+                #(#decode)*
+                ::core::result::Result::Ok(())
+            }
+        }
+    })
+}
@@ -0,0 +1,312 @@
+//! Command line tool for inspecting binary files.
+use binary_rw::{BinaryReader, Endian, FileStream, SeekStream};
+use std::error::Error;
+use std::process::ExitCode;
+
+type CliResult<T> = std::result::Result<T, Box<dyn Error>>;
+
+const USAGE: &str = "\
+usage:
+  binary_rw dump <file> [offset] [length]
+  binary_rw decode <file> <type@offset>...
+  binary_rw search <file> <hex bytes | text:string>
+  binary_rw layout <file> <layout file>
+
+types:
+  u8 i8 bool u16 i16 u32 i32 u64 i64 u128 i128 f32 f64 char
+  varint (7bit encoded u64) string (length-prefixed) string7 (7bit length-prefixed)
+  bytesN (N raw bytes)
+  multi-byte types take an le or be suffix, the default is le
+
+layout files have one field per line written as `name: type` or
+`name: type@offset`, fields without an offset follow the previous field
+and lines starting with # are ignored";
+
+/// Number of bytes shown on each line of a hex dump.
+const BYTES_PER_LINE: usize = 16;
+
+/// Number of bytes read at a time when dumping and searching.
+const CHUNK: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    U8,
+    I8,
+    Bool,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    U128,
+    I128,
+    F32,
+    F64,
+    Char,
+    Varint,
+    String,
+    String7,
+    Bytes(usize),
+}
+
+/// Type to decode, its endianness and an optional offset.
+struct Spec {
+    kind: Kind,
+    big_endian: bool,
+    offset: Option<usize>,
+}
+
+impl Spec {
+    fn endian(&self) -> Endian {
+        if self.big_endian {
+            Endian::Big
+        } else {
+            Endian::Little
+        }
+    }
+}
+
+fn parse_number(value: &str) -> CliResult<usize> {
+    let value = value.trim();
+    let number = match value.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    number.map_err(|_| format!("invalid number '{}'", value).into())
+}
+
+fn parse_kind(name: &str) -> Option<Kind> {
+    let kind = match name {
+        "u8" => Kind::U8,
+        "i8" => Kind::I8,
+        "bool" => Kind::Bool,
+        "u16" => Kind::U16,
+        "i16" => Kind::I16,
+        "u32" => Kind::U32,
+        "i32" => Kind::I32,
+        "u64" => Kind::U64,
+        "i64" => Kind::I64,
+        "u128" => Kind::U128,
+        "i128" => Kind::I128,
+        "f32" => Kind::F32,
+        "f64" => Kind::F64,
+        "char" => Kind::Char,
+        "varint" => Kind::Varint,
+        "string" => Kind::String,
+        "string7" => Kind::String7,
+        _ => Kind::Bytes(name.strip_prefix("bytes")?.parse().ok()?),
+    };
+    Some(kind)
+}
+
+/// Parse a type such as `u32le@0x10`, `varint@32` or `string7`.
+fn parse_spec(spec: &str) -> CliResult<Spec> {
+    let (name, offset) = match spec.split_once('@') {
+        Some((name, offset)) => (name, Some(parse_number(offset)?)),
+        None => (spec, None),
+    };
+    let (name, big_endian) = match parse_kind(name) {
+        Some(_) => (name, false),
+        None => match name.strip_suffix("le") {
+            Some(name) => (name, false),
+            None => (name.strip_suffix("be").unwrap_or(name), true),
+        },
+    };
+    let kind = parse_kind(name).ok_or_else(|| format!("unknown type '{}'", spec))?;
+    Ok(Spec {
+        kind,
+        big_endian,
+        offset,
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Decode a value at the current position and format it.
+fn decode(reader: &mut BinaryReader, kind: Kind) -> binary_rw::Result<String> {
+    let value = match kind {
+        Kind::U8 => reader.read_u8()?.to_string(),
+        Kind::I8 => reader.read_i8()?.to_string(),
+        Kind::Bool => reader.read_bool()?.to_string(),
+        Kind::U16 => reader.read_u16()?.to_string(),
+        Kind::I16 => reader.read_i16()?.to_string(),
+        Kind::U32 => reader.read_u32()?.to_string(),
+        Kind::I32 => reader.read_i32()?.to_string(),
+        Kind::U64 => reader.read_u64()?.to_string(),
+        Kind::I64 => reader.read_i64()?.to_string(),
+        Kind::U128 => reader.read_u128()?.to_string(),
+        Kind::I128 => reader.read_i128()?.to_string(),
+        Kind::F32 => reader.read_f32()?.to_string(),
+        Kind::F64 => reader.read_f64()?.to_string(),
+        Kind::Char => format!("{:?}", reader.read_char()?),
+        Kind::Varint => reader.read_7bit_encoded_u64()?.to_string(),
+        Kind::String => format!("{:?}", reader.read_string()?),
+        Kind::String7 => format!("{:?}", reader.read_7bit_encoded_len_string()?),
+        Kind::Bytes(length) => hex(&reader.read_bytes(length)?),
+    };
+    Ok(value)
+}
+
+fn dump(path: &str, offset: usize, length: Option<usize>) -> CliResult<()> {
+    let mut stream = FileStream::open(path)?.buffered(CHUNK);
+    let end = stream.len()?;
+    let end = length.map_or(end, |length| end.min(offset.saturating_add(length)));
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+    reader.seek(offset.min(end))?;
+
+    let mut position = offset;
+    while position < end {
+        let bytes = reader.read_bytes(BYTES_PER_LINE.min(end - position))?;
+        let text: String = bytes
+            .iter()
+            .map(|byte| {
+                if byte.is_ascii_graphic() || *byte == b' ' {
+                    *byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        println!(
+            "{:08x}  {:width$}  |{}|",
+            position,
+            hex(&bytes),
+            text,
+            width = BYTES_PER_LINE * 3 - 1
+        );
+        position += bytes.len();
+    }
+    Ok(())
+}
+
+fn decode_specs(path: &str, specs: &[String]) -> CliResult<()> {
+    let mut stream = FileStream::open(path)?;
+    for spec in specs {
+        let parsed = parse_spec(spec)?;
+        let mut reader = BinaryReader::new(&mut stream, parsed.endian());
+        if let Some(offset) = parsed.offset {
+            reader.seek(offset)?;
+        }
+        let offset = reader.tell()?;
+        let value = decode(&mut reader, parsed.kind)?;
+        println!("{:#010x}  {}  {}", offset, spec, value);
+    }
+    Ok(())
+}
+
+fn parse_pattern(pattern: &str) -> CliResult<Vec<u8>> {
+    if let Some(text) = pattern.strip_prefix("text:") {
+        return Ok(text.as_bytes().to_vec());
+    }
+    let digits: String = pattern.split_whitespace().collect();
+    if digits.is_empty() || !digits.is_ascii() || !digits.len().is_multiple_of(2) {
+        return Err(format!("invalid hex pattern '{}'", pattern).into());
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|index| {
+            u8::from_str_radix(&digits[index..index + 2], 16)
+                .map_err(|_| format!("invalid hex pattern '{}'", pattern).into())
+        })
+        .collect()
+}
+
+fn search(path: &str, pattern: &str) -> CliResult<()> {
+    let pattern = parse_pattern(pattern)?;
+    if pattern.is_empty() {
+        return Err("empty search pattern".into());
+    }
+    let chunk_size = CHUNK.max(pattern.len());
+    let mut stream = FileStream::open(path)?;
+    let length = stream.len()?;
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+
+    // Chunks overlap so matches spanning two chunks are found.
+    let mut start = 0;
+    while start + pattern.len() <= length {
+        reader.seek(start)?;
+        let chunk = reader.read_bytes(chunk_size.min(length - start))?;
+        for (index, window) in chunk.windows(pattern.len()).enumerate() {
+            if window == pattern.as_slice() {
+                println!("{:#010x}", start + index);
+            }
+        }
+        if start + chunk.len() == length {
+            break;
+        }
+        start += chunk.len() + 1 - pattern.len();
+    }
+    Ok(())
+}
+
+fn layout(path: &str, layout_path: &str) -> CliResult<()> {
+    let layout = std::fs::read_to_string(layout_path)?;
+    let mut fields = Vec::new();
+    for (number, line) in layout.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, spec) = line
+            .split_once(':')
+            .ok_or_else(|| format!("line {}: expected `name: type`", number + 1))?;
+        let spec = spec.trim();
+        fields.push((name.trim().to_string(), spec.to_string(), parse_spec(spec)?));
+    }
+
+    let name_width = fields.iter().map(|field| field.0.len()).max().unwrap_or(0);
+    let spec_width = fields.iter().map(|field| field.1.len()).max().unwrap_or(0);
+
+    let mut stream = FileStream::open(path)?;
+    for (name, spec, parsed) in &fields {
+        let mut reader = BinaryReader::new(&mut stream, parsed.endian());
+        if let Some(offset) = parsed.offset {
+            reader.seek(offset)?;
+        }
+        let offset = reader.tell()?;
+        let value = decode(&mut reader, parsed.kind)
+            .map_err(|error| format!("{} at offset {:#x}: {}", name, offset, error))?;
+        println!(
+            "{:#010x}  {:name_width$}  {:spec_width$}  {}",
+            offset, name, spec, value
+        );
+    }
+    Ok(())
+}
+
+fn run(args: &[String]) -> CliResult<()> {
+    match args {
+        [command, path, rest @ ..] if command == "dump" && rest.len() <= 2 => {
+            let offset = rest
+                .first()
+                .map(|offset| parse_number(offset))
+                .transpose()?;
+            let length = rest.get(1).map(|length| parse_number(length)).transpose()?;
+            dump(path, offset.unwrap_or(0), length)
+        }
+        [command, path, specs @ ..] if command == "decode" && !specs.is_empty() => {
+            decode_specs(path, specs)
+        }
+        [command, path, pattern] if command == "search" => search(path, pattern),
+        [command, path, layout_path] if command == "layout" => layout(path, layout_path),
+        _ => Err(USAGE.into()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Bulk reads and writes of primitive slices.
+use crate::{BinaryReader, BinaryWriter, Decode, Encode, Result};
+use std::fmt::Debug;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Trait for primitives that can be read and written in bulk.
+///
+/// Implemented for the integer and floating point types, which have
+/// no padding and no invalid bit patterns so a slice of them can be
+/// filled directly from the stream. This trait is sealed.
+pub trait Primitive: Copy + Default + Debug + sealed::Sealed {
+    /// Reverse the byte order of the value.
+    fn swap_bytes(self) -> Self;
+}
+
+macro_rules! primitive {
+    ($($kind:ty),*) => {
+        $(
+            impl sealed::Sealed for $kind {}
+
+            impl Primitive for $kind {
+                fn swap_bytes(self) -> Self {
+                    <$kind>::swap_bytes(self)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! primitive_float {
+    ($($kind:ty),*) => {
+        $(
+            impl sealed::Sealed for $kind {}
+
+            impl Primitive for $kind {
+                fn swap_bytes(self) -> Self {
+                    <$kind>::from_bits(self.to_bits().swap_bytes())
+                }
+            }
+        )*
+    };
+}
+
+primitive!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
+primitive_float!(f32, f64);
+
+/// Number of elements converted at a time when writing with
+/// a byte order that differs from the native byte order.
+const CHUNK: usize = 1024;
+
+fn as_bytes<T: Primitive>(values: &[T]) -> &[u8] {
+    // SAFETY: primitives have no padding so every byte is initialized.
+    unsafe {
+        std::slice::from_raw_parts(values.as_ptr() as *const u8, std::mem::size_of_val(values))
+    }
+}
+
+fn as_bytes_mut<T: Primitive>(values: &mut [T]) -> &mut [u8] {
+    // SAFETY: primitives have no padding and every bit pattern is valid.
+    unsafe {
+        std::slice::from_raw_parts_mut(
+            values.as_mut_ptr() as *mut u8,
+            std::mem::size_of_val(values),
+        )
+    }
+}
+
+impl<'a> BinaryReader<'a> {
+    /// Fill a slice of primitives with a single read from the stream.
+    ///
+    /// Values are swapped in place only when the endianness of the
+    /// reader differs from the native byte order.
+    pub fn read_into_slice<T: Primitive>(&mut self, values: &mut [T]) -> Result<()> {
+        self.traced("read_into_slice", |reader| {
+            reader.fill(as_bytes_mut(values))?;
+            if std::mem::size_of::<T>() > 1 && !reader.endian.is_native() {
+                for value in values.iter_mut() {
+                    *value = value.swap_bytes();
+                }
+            }
+            Ok(values)
+        })?;
+        Ok(())
+    }
+
+    /// Read `count` primitives into a vector.
+    ///
+    /// The count and the size of the allocation are checked against
+    /// the reader limits before allocating.
+    pub fn read_vec<T: Primitive>(&mut self, count: usize) -> Result<Vec<T>> {
+        self.traced("read_vec", |reader| {
+            reader.check_elements(count)?;
+            reader.check_allocation(count.saturating_mul(std::mem::size_of::<T>()))?;
+            let mut values = vec![T::default(); count];
+            reader.read_into_slice(&mut values)?;
+            Ok(values)
+        })
+    }
+}
+
+impl<'a> BinaryWriter<'a> {
+    /// Write a slice of primitives to the stream.
+    ///
+    /// When the endianness of the writer matches the native byte
+    /// order the slice is written with a single write, otherwise
+    /// values are converted in chunks.
+    pub fn write_slice<T: Primitive>(&mut self, values: &[T]) -> Result<usize> {
+        if std::mem::size_of::<T>() == 1 || self.endian.is_native() {
+            self.stream.write_all(as_bytes(values))?;
+        } else {
+            let mut swapped = Vec::with_capacity(values.len().min(CHUNK));
+            for chunk in values.chunks(CHUNK) {
+                swapped.clear();
+                swapped.extend(chunk.iter().map(|value| value.swap_bytes()));
+                self.stream.write_all(as_bytes(&swapped))?;
+            }
+        }
+        Ok(std::mem::size_of_val(values))
+    }
+}
+
+macro_rules! encode_primitive {
+    ($($kind:ty),*) => {
+        $(
+            impl Encode for $kind {
+                fn encode(&self, writer: &mut BinaryWriter) -> Result<()> {
+                    writer.write_slice(std::slice::from_ref(self))?;
+                    Ok(())
+                }
+            }
+
+            impl Decode for $kind {
+                fn decode(&mut self, reader: &mut BinaryReader) -> Result<()> {
+                    reader.read_into_slice(std::slice::from_mut(self))
+                }
+            }
+        )*
+    };
+}
+
+// `usize` and `isize` are left out as their size depends on the target.
+encode_primitive!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, f32, f64);
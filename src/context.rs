@@ -0,0 +1,70 @@
+//! Field paths attached to errors generated while decoding.
+use crate::{BinaryError, BinaryReader, Result};
+
+/// Segment of the path to the field being decoded.
+pub(crate) enum Segment {
+    Field(&'static str),
+    Element(usize),
+}
+
+impl<'a> BinaryReader<'a> {
+    /// Decode a named field.
+    ///
+    /// Errors generated inside `read` are annotated with the path
+    /// to the field and the offset it started at, for example
+    /// `header.entries[3].name at offset 0x1a4: ...`.
+    pub fn field<T, F>(&mut self, name: &'static str, read: F) -> Result<T>
+    where
+        F: FnOnce(&mut BinaryReader) -> Result<T>,
+    {
+        self.scope(Segment::Field(name), read)
+    }
+
+    /// Decode an element of a collection.
+    ///
+    /// Errors generated inside `read` are annotated in the same
+    /// way as `field()` using the index of the element.
+    pub fn element<T, F>(&mut self, index: usize, read: F) -> Result<T>
+    where
+        F: FnOnce(&mut BinaryReader) -> Result<T>,
+    {
+        self.scope(Segment::Element(index), read)
+    }
+
+    /// Get the path to the field being decoded.
+    pub fn path(&self) -> String {
+        let mut path = String::new();
+        for segment in &self.path {
+            match segment {
+                Segment::Field(name) => {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(name);
+                }
+                Segment::Element(index) => {
+                    path.push_str(&format!("[{}]", index));
+                }
+            }
+        }
+        path
+    }
+
+    fn scope<T, F>(&mut self, segment: Segment, read: F) -> Result<T>
+    where
+        F: FnOnce(&mut BinaryReader) -> Result<T>,
+    {
+        let offset = self.stream.tell()?;
+        self.path.push(segment);
+        let result = read(self).map_err(|error| match error {
+            BinaryError::Context { .. } => error,
+            error => BinaryError::Context {
+                path: self.path(),
+                offset,
+                source: Box::new(error),
+            },
+        });
+        self.path.pop();
+        result
+    }
+}
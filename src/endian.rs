@@ -0,0 +1,176 @@
+//! Reads, writes and values with an explicit endianness.
+use crate::{BinaryReader, BinaryWriter, Decode, Encode, Endian, Primitive, Result};
+use std::borrow::Borrow;
+
+macro_rules! read_endian {
+    ($kind:ty, $read:ident, $le:ident, $be:ident) => {
+        #[doc = concat!("Read a little endian `", stringify!($kind), "` regardless of the endianness of the reader.")]
+        pub fn $le(&mut self) -> Result<$kind> {
+            self.with_endian(Endian::Little, |reader| reader.$read())
+        }
+
+        #[doc = concat!("Read a big endian `", stringify!($kind), "` regardless of the endianness of the reader.")]
+        pub fn $be(&mut self) -> Result<$kind> {
+            self.with_endian(Endian::Big, |reader| reader.$read())
+        }
+    };
+}
+
+macro_rules! write_endian {
+    ($kind:ty, $write:ident, $le:ident, $be:ident) => {
+        #[doc = concat!("Write a little endian `", stringify!($kind), "` regardless of the endianness of the writer.")]
+        pub fn $le<V: Borrow<$kind>>(&mut self, value: V) -> Result<usize> {
+            self.with_endian(Endian::Little, |writer| writer.$write(value))
+        }
+
+        #[doc = concat!("Write a big endian `", stringify!($kind), "` regardless of the endianness of the writer.")]
+        pub fn $be<V: Borrow<$kind>>(&mut self, value: V) -> Result<usize> {
+            self.with_endian(Endian::Big, |writer| writer.$write(value))
+        }
+    };
+}
+
+impl<'a> BinaryReader<'a> {
+    /// Run a read with a different endianness.
+    fn with_endian<T, F>(&mut self, endian: Endian, read: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        let previous = std::mem::replace(&mut self.endian, endian);
+        let result = read(self);
+        self.endian = previous;
+        result
+    }
+
+    read_endian!(u16, read_u16, read_u16_le, read_u16_be);
+    read_endian!(i16, read_i16, read_i16_le, read_i16_be);
+    read_endian!(u32, read_u32, read_u32_le, read_u32_be);
+    read_endian!(i32, read_i32, read_i32_le, read_i32_be);
+    read_endian!(u64, read_u64, read_u64_le, read_u64_be);
+    read_endian!(i64, read_i64, read_i64_le, read_i64_be);
+    read_endian!(u128, read_u128, read_u128_le, read_u128_be);
+    read_endian!(i128, read_i128, read_i128_le, read_i128_be);
+    read_endian!(usize, read_usize, read_usize_le, read_usize_be);
+    read_endian!(isize, read_isize, read_isize_le, read_isize_be);
+    read_endian!(f32, read_f32, read_f32_le, read_f32_be);
+    read_endian!(f64, read_f64, read_f64_le, read_f64_be);
+    read_endian!(char, read_char, read_char_le, read_char_be);
+}
+
+impl<'a> BinaryWriter<'a> {
+    /// Run a write with a different endianness.
+    fn with_endian<F>(&mut self, endian: Endian, write: F) -> Result<usize>
+    where
+        F: FnOnce(&mut Self) -> Result<usize>,
+    {
+        let previous = std::mem::replace(&mut self.endian, endian);
+        let result = write(self);
+        self.endian = previous;
+        result
+    }
+
+    write_endian!(u16, write_u16, write_u16_le, write_u16_be);
+    write_endian!(i16, write_i16, write_i16_le, write_i16_be);
+    write_endian!(u32, write_u32, write_u32_le, write_u32_be);
+    write_endian!(i32, write_i32, write_i32_le, write_i32_be);
+    write_endian!(u64, write_u64, write_u64_le, write_u64_be);
+    write_endian!(i64, write_i64, write_i64_le, write_i64_be);
+    write_endian!(u128, write_u128, write_u128_le, write_u128_be);
+    write_endian!(i128, write_i128, write_i128_le, write_i128_be);
+    write_endian!(usize, write_usize, write_usize_le, write_usize_be);
+    write_endian!(isize, write_isize, write_isize_le, write_isize_be);
+    write_endian!(f32, write_f32, write_f32_le, write_f32_be);
+    write_endian!(f64, write_f64, write_f64_le, write_f64_be);
+    write_endian!(char, write_char, write_char_le, write_char_be);
+}
+
+macro_rules! endian_wrapper {
+    ($name:ident, $endian:expr, $order:literal) => {
+        #[doc = concat!("Value that is always encoded ", $order, " endian.")]
+        ///
+        /// The byte order is part of the type so it does not depend on the
+        /// endianness of the reader or writer. The value is stored in native
+        /// form and accessed with `get()` and `set()`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+        pub struct $name<T: Primitive>(T);
+
+        impl<T: Primitive> $name<T> {
+            /// Wrap a value.
+            pub fn new(value: T) -> Self {
+                Self(value)
+            }
+
+            /// Get the value.
+            pub fn get(self) -> T {
+                self.0
+            }
+
+            /// Set the value.
+            pub fn set(&mut self, value: T) {
+                self.0 = value;
+            }
+        }
+
+        impl<T: Primitive> From<T> for $name<T> {
+            fn from(value: T) -> Self {
+                Self(value)
+            }
+        }
+
+        impl<T: Primitive> Encode for $name<T> {
+            fn encode(&self, writer: &mut BinaryWriter) -> Result<()> {
+                writer.with_endian($endian, |writer| {
+                    writer.write_slice(std::slice::from_ref(&self.0))
+                })?;
+                Ok(())
+            }
+        }
+
+        impl<T: Primitive> Decode for $name<T> {
+            fn decode(&mut self, reader: &mut BinaryReader) -> Result<()> {
+                reader.with_endian($endian, |reader| {
+                    reader.read_into_slice(std::slice::from_mut(&mut self.0))
+                })
+            }
+        }
+    };
+}
+
+endian_wrapper!(Le, Endian::Little, "little");
+endian_wrapper!(Be, Endian::Big, "big");
+
+macro_rules! endian_aliases {
+    ($($kind:ty, $le:ident, $be:ident);* $(;)?) => {
+        $(
+            #[doc = concat!("Little endian `", stringify!($kind), "`.")]
+            pub type $le = Le<$kind>;
+            #[doc = concat!("Big endian `", stringify!($kind), "`.")]
+            pub type $be = Be<$kind>;
+
+            impl From<Le<$kind>> for $kind {
+                fn from(value: Le<$kind>) -> Self {
+                    value.get()
+                }
+            }
+
+            impl From<Be<$kind>> for $kind {
+                fn from(value: Be<$kind>) -> Self {
+                    value.get()
+                }
+            }
+        )*
+    };
+}
+
+endian_aliases!(
+    u16, U16Le, U16Be;
+    i16, I16Le, I16Be;
+    u32, U32Le, U32Be;
+    i32, I32Le, I32Be;
+    u64, U64Le, U64Be;
+    i64, I64Le, I64Be;
+    u128, U128Le, U128Be;
+    i128, I128Le, I128Be;
+    f32, F32Le, F32Be;
+    f64, F64Le, F64Be;
+);
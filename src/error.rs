@@ -4,11 +4,190 @@ use thiserror::Error;
 #[derive(Debug, Error)]
 pub enum BinaryError {
     /// Error generated attempted to read past the end of file.
-    #[error("attempt to read past EOF")]
-    ReadPastEof,
+    #[error(
+        "attempt to read {requested} bytes past EOF at offset {offset:#x}, {available} available"
+    )]
+    ReadPastEof {
+        /// Stream offset the read started at.
+        offset: usize,
+        /// Number of bytes requested.
+        requested: usize,
+        /// Number of bytes available before the end of the stream.
+        available: usize,
+    },
+    /// Error generated attempted to write past the end of a fixed size stream.
+    #[error("attempt to write past the end of a fixed size stream, buffer is full")]
+    WritePastEnd,
+    /// Error generated seeking past the end of a stream that does not allow it.
+    #[error("attempt to seek to {position} past the end of a stream of {length} bytes")]
+    SeekOutOfBounds {
+        /// Position that was requested.
+        position: usize,
+        /// Length of the stream.
+        length: usize,
+    },
+    /// Error generated when a length-prefixed block ends before it starts.
+    #[error("length-prefixed block starting at {start} ended at {end}")]
+    BlockUnderflow {
+        /// Position of the start of the block.
+        start: usize,
+        /// Position of the stream when the block ended.
+        end: usize,
+    },
+    /// Error generated when an allocation would exceed the reader limits.
+    #[error("allocation of {requested} bytes exceeds the limit of {limit} bytes")]
+    AllocationLimit {
+        /// Number of bytes requested.
+        requested: usize,
+        /// Limit that was exceeded.
+        limit: usize,
+    },
+    /// Error generated when a collection has more elements than the reader limits.
+    #[error("collection of {count} elements exceeds the limit of {limit} elements")]
+    ElementLimit {
+        /// Number of elements in the collection.
+        count: usize,
+        /// Limit that was exceeded.
+        limit: usize,
+    },
+    /// Error generated when a collection does not have the number of
+    /// elements given by its count.
+    #[error("collection has {actual} elements but its count is {expected}")]
+    CountMismatch {
+        /// Number of elements given by the count.
+        expected: usize,
+        /// Number of elements in the collection.
+        actual: usize,
+    },
+    /// Error generated when a field does not satisfy an assertion.
+    #[error("assertion failed: {0}")]
+    AssertionFailed(&'static str),
+    /// Error generated when a magic number or signature does not match.
+    #[error("expected {expected:02x?} at offset {offset:#x} but found {actual:02x?}")]
+    UnexpectedBytes {
+        /// Stream offset the bytes were read from.
+        offset: usize,
+        /// Bytes that were expected.
+        expected: Vec<u8>,
+        /// Bytes that were read.
+        actual: Vec<u8>,
+    },
+    /// Error generated reading a version newer than the type supports.
+    #[error("version {version} is newer than the supported version {supported}")]
+    VersionTooNew {
+        /// Version that was read.
+        version: u32,
+        /// Newest version the type supports.
+        supported: u32,
+    },
+    /// Error generated reading an older version the type cannot decode.
+    #[error("version {0} is no longer supported")]
+    UnsupportedVersion(u32),
+    /// Error generated when a frame is larger than the maximum frame size.
+    #[error("frame of {length} bytes exceeds the maximum of {max} bytes")]
+    FrameTooLarge {
+        /// Length of the frame payload.
+        length: usize,
+        /// Maximum frame size.
+        max: usize,
+    },
+    /// Error generated when the stream ends part way through a frame.
+    #[error("frame at offset {offset:#x} was truncated after {received} bytes")]
+    TruncatedFrame {
+        /// Stream offset of the start of the frame.
+        offset: usize,
+        /// Number of bytes of the frame that were received.
+        received: usize,
+    },
     /// Error generated trying to read the char type.
     #[error("invalid character read from stream")]
     InvalidChar,
+    /// Error generated when a 7bit encoded value has more bits than its type.
+    #[error("7bit encoded value does not fit in {bits} bits")]
+    VarintTooLong {
+        /// Number of bits in the type being read.
+        bits: u32,
+    },
+    /// Error generated when a label is defined more than once.
+    #[error("label '{0}' is already defined")]
+    DuplicateLabel(String),
+    /// Error generated when a referenced label was never defined.
+    #[error("label '{0}' is not defined")]
+    UndefinedLabel(String),
+    /// Error generated when the offset to a label does not fit the reference.
+    #[error("offset to label '{0}' does not fit the reference")]
+    LabelOverflow(String),
+    /// Error generated restoring a position that was never saved.
+    #[error("no saved position to restore")]
+    PositionStackEmpty,
+    /// Error generated when an offset added to the base overflows.
+    #[error("offset {offset:#x} from base {base:#x} overflows")]
+    OffsetOverflow {
+        /// Base the offset is relative to.
+        base: usize,
+        /// Offset that was added to the base.
+        offset: usize,
+    },
+    /// Error generated when a checksum trailer does not match the data.
+    #[error("checksum mismatch, expected {expected:#x} but computed {actual:#x}")]
+    ChecksumMismatch {
+        /// Checksum stored in the trailer.
+        expected: u64,
+        /// Checksum computed over the data.
+        actual: u64,
+    },
+    /// Error generated when a record in a log fails its checksum.
+    #[error("record at offset {offset:#x} is corrupt")]
+    CorruptRecord {
+        /// Offset of the record in the log.
+        offset: usize,
+    },
+    /// Error generated when the size of a record file does not match
+    /// its header and record sizes.
+    #[error(
+        "file of {length} bytes is not a {header} byte header followed by {record} byte records"
+    )]
+    InvalidRecordFile {
+        /// Length of the file.
+        length: usize,
+        /// Size of the header.
+        header: usize,
+        /// Size of each record.
+        record: usize,
+    },
+    /// Error generated when a record is not encoded in its fixed size.
+    #[error("record encoded in {actual} bytes but its size is {expected} bytes")]
+    RecordSize {
+        /// Fixed size of the record.
+        expected: usize,
+        /// Number of bytes encoded or decoded.
+        actual: usize,
+    },
+    /// Error generated when accessing a record past the end of a file.
+    #[error("record index {index} is out of bounds for {len} records")]
+    IndexOutOfBounds {
+        /// Index that was accessed.
+        index: usize,
+        /// Number of records.
+        len: usize,
+    },
+    /// Error generated when a stream does not support an operation.
+    #[error("operation not supported: {0}")]
+    Unsupported(&'static str),
+    /// Error annotated with the path to the field being decoded.
+    #[error("{path} at offset {offset:#x}: {source}")]
+    Context {
+        /// Path to the field, for example `header.entries[3].name`.
+        path: String,
+        /// Stream offset the field started at.
+        offset: usize,
+        /// Error generated decoding the field.
+        source: Box<BinaryError>,
+    },
+    /// Error generated by a serde `Serialize` or `Deserialize` implementation.
+    #[cfg(feature = "serde")]
+    #[error("{0}")]
+    Serde(String),
     /// Error generated converting between integers.
     #[error(transparent)]
     TryFromInt(#[from] std::num::TryFromIntError),
@@ -17,5 +196,32 @@ pub enum BinaryError {
     Utf8Error(#[from] std::string::FromUtf8Error),
     /// Error generated by input / output.
     #[error(transparent)]
-    Io(#[from] std::io::Error),
+    Io(std::io::Error),
+}
+
+impl BinaryError {
+    /// Get the underlying error without any field context.
+    pub fn root(&self) -> &BinaryError {
+        match self {
+            BinaryError::Context { source, .. } => source.root(),
+            error => error,
+        }
+    }
+}
+
+impl From<std::io::Error> for BinaryError {
+    /// Streams report their own errors through `std::io::Error`,
+    /// those are unwrapped so they can be matched directly.
+    fn from(error: std::io::Error) -> Self {
+        if error
+            .get_ref()
+            .is_some_and(|inner| inner.is::<BinaryError>())
+        {
+            return *error
+                .into_inner()
+                .and_then(|inner| inner.downcast().ok())
+                .expect("inner error is a BinaryError");
+        }
+        BinaryError::Io(error)
+    }
 }
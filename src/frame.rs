@@ -0,0 +1,192 @@
+//! Length-delimited frames for exchanging messages over streams.
+use crate::{
+    BinaryError, BinaryReader, BinaryWriter, Decode, Encode, LengthPrefix, MemoryStream, Result,
+    SliceStream,
+};
+
+/// Format of length-delimited frames.
+///
+/// Each frame is the length of the payload followed by the payload,
+/// frames with a payload larger than `max_frame_size` are rejected
+/// by both the reader and the writer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Framing {
+    /// Encoding of the payload length.
+    pub prefix: LengthPrefix,
+    /// Maximum number of bytes in a payload.
+    pub max_frame_size: usize,
+}
+
+impl Framing {
+    /// Create a frame format.
+    pub fn new(prefix: LengthPrefix, max_frame_size: usize) -> Self {
+        Self {
+            prefix,
+            max_frame_size,
+        }
+    }
+
+    fn check(&self, length: usize) -> Result<()> {
+        if length > self.max_frame_size {
+            return Err(BinaryError::FrameTooLarge {
+                length,
+                max: self.max_frame_size,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<'a> BinaryReader<'a> {
+    /// Read a frame and decode its payload.
+    ///
+    /// Returns `None` at the end of the stream when no bytes of the
+    /// next frame were read. Bytes left in the payload after decoding
+    /// are ignored.
+    pub fn read_frame<T: Decode + Default>(&mut self, framing: &Framing) -> Result<Option<T>> {
+        let payload = match self.read_frame_bytes(framing)? {
+            Some(payload) => payload,
+            None => return Ok(None),
+        };
+        let mut stream = SliceStream::new(&payload);
+        let mut reader = BinaryReader::new(&mut stream, self.endian);
+        reader.set_limits(self.limits);
+        let mut value = T::default();
+        value.decode(&mut reader)?;
+        Ok(Some(value))
+    }
+
+    /// Read a frame and return its payload.
+    ///
+    /// Returns `None` at the end of the stream when no bytes of the
+    /// next frame were read. A frame that ends early fails with
+    /// `BinaryError::TruncatedFrame` and a length larger than the
+    /// maximum fails with `BinaryError::FrameTooLarge` before the
+    /// payload is read.
+    pub fn read_frame_bytes(&mut self, framing: &Framing) -> Result<Option<Vec<u8>>> {
+        self.traced("read_frame_bytes", |reader| {
+            let offset = reader.stream.tell()?;
+            let result = reader.read_frame_payload(framing);
+            match result {
+                Err(BinaryError::ReadPastEof { available, .. }) => {
+                    Err(BinaryError::TruncatedFrame {
+                        offset,
+                        received: reader.stream.tell()? - offset + available,
+                    })
+                }
+                Err(BinaryError::Io(error))
+                    if error.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    Err(BinaryError::TruncatedFrame {
+                        offset,
+                        received: reader.stream.tell()? - offset,
+                    })
+                }
+                result => result,
+            }
+        })
+    }
+
+    fn read_frame_payload(&mut self, framing: &Framing) -> Result<Option<Vec<u8>>> {
+        let first = match self.read_frame_start()? {
+            Some(first) => first,
+            None => return Ok(None),
+        };
+        let length = match framing.prefix {
+            LengthPrefix::U16 => self.read_frame_length(first, 2)?,
+            LengthPrefix::U32 => self.read_frame_length(first, 4)?,
+            LengthPrefix::U64 => self.read_frame_length(first, 8)?,
+            LengthPrefix::Varint => {
+                let mut byte = first;
+                let mut length = (byte & 0x7F) as usize;
+                let mut shift = 7;
+                while byte & 0x80 != 0 {
+                    if shift >= usize::BITS {
+                        return Err(BinaryError::FrameTooLarge {
+                            length: usize::MAX,
+                            max: framing.max_frame_size,
+                        });
+                    }
+                    byte = self.read_u8()?;
+                    length |= ((byte & 0x7F) as usize) << shift;
+                    shift += 7;
+                }
+                length
+            }
+        };
+        framing.check(length)?;
+        self.check_allocation(length)?;
+        let mut payload = vec![0; length];
+        self.fill(&mut payload)?;
+        Ok(Some(payload))
+    }
+
+    /// Read the first byte of a frame, `None` at the end of the stream.
+    fn read_frame_start(&mut self) -> Result<Option<u8>> {
+        if let Ok(length) = self.stream.len() {
+            if self.stream.tell()? >= length {
+                return Ok(None);
+            }
+        }
+        let mut byte = [0u8; 1];
+        loop {
+            match self.stream.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            }
+        }
+        self.record(&byte);
+        Ok(Some(byte[0]))
+    }
+
+    /// Read the rest of a fixed width length given its first byte.
+    fn read_frame_length(&mut self, first: u8, width: usize) -> Result<usize> {
+        let mut bytes = [0u8; 8];
+        let length = if self.endian.is_little() {
+            bytes[0] = first;
+            self.fill(&mut bytes[1..width])?;
+            u64::from_le_bytes(bytes)
+        } else {
+            bytes[8 - width] = first;
+            self.fill(&mut bytes[9 - width..])?;
+            u64::from_be_bytes(bytes)
+        };
+        Ok(usize::try_from(length)?)
+    }
+}
+
+impl<'a> BinaryWriter<'a> {
+    /// Encode a value and write it as a frame.
+    ///
+    /// The value is encoded into a buffer first so the stream does
+    /// not need to support seeking.
+    pub fn write_frame<T: Encode + ?Sized>(
+        &mut self,
+        framing: &Framing,
+        value: &T,
+    ) -> Result<usize> {
+        let mut buffer = MemoryStream::new();
+        value.encode(&mut BinaryWriter::new(&mut buffer, self.endian))?;
+        self.write_frame_bytes(framing, buffer.as_slice())
+    }
+
+    /// Write a payload as a frame.
+    pub fn write_frame_bytes<B: AsRef<[u8]>>(
+        &mut self,
+        framing: &Framing,
+        payload: B,
+    ) -> Result<usize> {
+        let payload = payload.as_ref();
+        framing.check(payload.len())?;
+        let written = match framing.prefix {
+            LengthPrefix::U16 => self.write_u16(u16::try_from(payload.len())?)?,
+            LengthPrefix::U32 => self.write_u32(u32::try_from(payload.len())?)?,
+            LengthPrefix::U64 => self.write_u64(payload.len() as u64)?,
+            LengthPrefix::Varint => self.write_7bit_encoded_usize(payload.len())?,
+        };
+        self.stream.write_all(payload)?;
+        Ok(written + payload.len())
+    }
+}
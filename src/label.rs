@@ -0,0 +1,167 @@
+//! Labels for forward references resolved when writing is finished.
+use crate::{BinaryError, BinaryWriter, Result};
+use std::collections::HashMap;
+
+/// How the offset to a label is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffsetKind {
+    /// Offset from the start of the stream.
+    Absolute,
+    /// Offset from the position of the reference itself.
+    ///
+    /// The label must be defined at or after the reference.
+    Relative,
+}
+
+/// Width of the offset written for a label reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffsetWidth {
+    /// Offset is written as a `u8`.
+    U8,
+    /// Offset is written as a `u16`.
+    U16,
+    /// Offset is written as a `u32`.
+    U32,
+    /// Offset is written as a `u64`.
+    U64,
+}
+
+impl OffsetWidth {
+    fn size(&self) -> usize {
+        match self {
+            OffsetWidth::U8 => 1,
+            OffsetWidth::U16 => 2,
+            OffsetWidth::U32 => 4,
+            OffsetWidth::U64 => 8,
+        }
+    }
+}
+
+/// Reference to a label waiting to be resolved.
+struct Fixup {
+    label: String,
+    position: usize,
+    kind: OffsetKind,
+    width: OffsetWidth,
+}
+
+/// Labels and pending fixups owned by a writer.
+#[derive(Default)]
+pub(crate) struct Labels {
+    defined: HashMap<String, usize>,
+    fixups: Vec<Fixup>,
+}
+
+impl Labels {
+    /// Move labels and references from a writer whose stream is
+    /// copied into this writer's stream at `base`.
+    ///
+    /// Fails without merging anything if a label is defined twice.
+    pub(crate) fn merge(&mut self, other: Labels, base: usize) -> Result<()> {
+        if let Some(label) = other
+            .defined
+            .keys()
+            .find(|label| self.defined.contains_key(*label))
+        {
+            return Err(BinaryError::DuplicateLabel(label.clone()));
+        }
+        for (label, position) in other.defined {
+            self.defined.insert(label, base + position);
+        }
+        for mut fixup in other.fixups {
+            fixup.position += base;
+            self.fixups.push(fixup);
+        }
+        Ok(())
+    }
+
+    /// Compute the offset written for a reference.
+    fn resolve(&self, fixup: &Fixup) -> Result<u64> {
+        let target = *self
+            .defined
+            .get(&fixup.label)
+            .ok_or_else(|| BinaryError::UndefinedLabel(fixup.label.clone()))?;
+        let offset = match fixup.kind {
+            OffsetKind::Absolute => Some(target),
+            OffsetKind::Relative => target.checked_sub(fixup.position),
+        };
+        let max = match fixup.width {
+            OffsetWidth::U8 => u8::MAX as u64,
+            OffsetWidth::U16 => u16::MAX as u64,
+            OffsetWidth::U32 => u32::MAX as u64,
+            OffsetWidth::U64 => u64::MAX,
+        };
+        offset
+            .map(|offset| offset as u64)
+            .filter(|offset| *offset <= max)
+            .ok_or_else(|| BinaryError::LabelOverflow(fixup.label.clone()))
+    }
+}
+
+impl<'a> BinaryWriter<'a> {
+    /// Write a reference to a label that is resolved by `finish()`.
+    ///
+    /// A zeroed slot of the given width is written in place
+    /// of the offset.
+    pub fn write_label_ref<S: Into<String>>(
+        &mut self,
+        label: S,
+        kind: OffsetKind,
+        width: OffsetWidth,
+    ) -> Result<usize> {
+        let position = self.stream.tell()?;
+        let written = self.write_bytes_with_value(width.size(), 0)?;
+        self.labels.fixups.push(Fixup {
+            label: label.into(),
+            position,
+            kind,
+            width,
+        });
+        Ok(written)
+    }
+
+    /// Define a label at the current stream position.
+    pub fn define_label<S: Into<String>>(&mut self, label: S) -> Result<()> {
+        let label = label.into();
+        if self.labels.defined.contains_key(&label) {
+            return Err(BinaryError::DuplicateLabel(label));
+        }
+        let position = self.stream.tell()?;
+        self.labels.defined.insert(label, position);
+        Ok(())
+    }
+
+    /// Get the position of a defined label.
+    pub fn label_position(&self, label: &str) -> Option<usize> {
+        self.labels.defined.get(label).copied()
+    }
+
+    /// Resolve all pending label references.
+    ///
+    /// Every reference is checked before any offset is written so
+    /// a failed call leaves the stream and pending references
+    /// untouched. The stream position is restored once the offsets
+    /// have been written.
+    pub fn finish(&mut self) -> Result<()> {
+        let mut offsets = Vec::with_capacity(self.labels.fixups.len());
+        for fixup in &self.labels.fixups {
+            offsets.push(self.labels.resolve(fixup)?);
+        }
+
+        let position = self.stream.tell()?;
+        for (fixup, offset) in std::mem::take(&mut self.labels.fixups)
+            .into_iter()
+            .zip(offsets)
+        {
+            self.stream.seek(fixup.position)?;
+            match fixup.width {
+                OffsetWidth::U8 => self.write_u8(offset as u8)?,
+                OffsetWidth::U16 => self.write_u16(offset as u16)?,
+                OffsetWidth::U32 => self.write_u32(offset as u32)?,
+                OffsetWidth::U64 => self.write_u64(offset)?,
+            };
+        }
+        self.stream.seek(position)?;
+        Ok(())
+    }
+}
@@ -16,22 +16,63 @@ use std::{
     io::{Read, Write},
 };
 
+mod bulk;
+mod context;
+mod endian;
 mod error;
+mod frame;
+mod label;
+mod limits;
+mod magic;
+mod placeholder;
+mod position;
+mod record_file;
+mod record_log;
+#[cfg(feature = "serde")]
+mod serialize;
 mod stream;
-
+mod trace;
+mod version;
+
+#[cfg(feature = "derive")]
+pub use binary_rw_derive::{Decode, Encode};
+pub use bulk::Primitive;
+pub use endian::{
+    Be, F32Be, F32Le, F64Be, F64Le, I128Be, I128Le, I16Be, I16Le, I32Be, I32Le, I64Be, I64Le, Le,
+    U128Be, U128Le, U16Be, U16Le, U32Be, U32Le, U64Be, U64Le,
+};
 pub use error::BinaryError;
+pub use frame::Framing;
+pub use label::{OffsetKind, OffsetWidth};
+pub use limits::Limits;
+pub use placeholder::{LengthPrefix, Placeholder, Reservable};
+pub use record_file::{FixedSize, RecordFile, RecordFileIter};
+pub use record_log::{RecordLog, Records, SyncPolicy};
+#[cfg(feature = "serde")]
+pub use serialize::{from_slice, to_vec, Deserializer, LengthEncoding, Serializer};
+pub use stream::atomic::AtomicFile;
+pub use stream::checksum::{Adler32, Checksum, ChecksumStream, Crc32, Crc32c, HasherChecksum};
+#[cfg(any(feature = "deflate", feature = "zstd"))]
+pub use stream::compression::{Codec, CompressReader, CompressWriter};
 pub use stream::file::FileStream;
+pub use stream::io::IoStream;
 pub use stream::memory::MemoryStream;
-pub use stream::slice::SliceStream;
+#[cfg(feature = "mmap")]
+pub use stream::mmap::{MmapStream, MmapStreamMut};
+pub use stream::slice::{SliceStream, SliceStreamMut};
+pub use stream::SeekPastEnd;
+pub use trace::{Trace, TraceEntry};
+pub use version::Versioned;
 
 /// Result type for binary errors.
 pub type Result<T> = std::result::Result<T, BinaryError>;
 
 macro_rules! encode {
     ($endian:expr, $value:expr, $stream:expr) => {
-        let data = match $endian {
-            Endian::Little => $value.to_le_bytes(),
-            Endian::Big => $value.to_be_bytes(),
+        let data = if $endian.is_little() {
+            $value.to_le_bytes()
+        } else {
+            $value.to_be_bytes()
         };
         return Ok($stream.write(&data)?);
     };
@@ -39,30 +80,76 @@ macro_rules! encode {
 
 macro_rules! decode {
     ($endian:expr, $value:expr, $kind:ty) => {
-        let data = match $endian {
-            Endian::Little => <$kind>::from_le_bytes($value),
-            Endian::Big => <$kind>::from_be_bytes($value),
+        let data = if $endian.is_little() {
+            <$kind>::from_le_bytes($value)
+        } else {
+            <$kind>::from_be_bytes($value)
         };
         return Ok(data);
     };
 }
 
 /// Variants to describe endianness.
-#[derive(PartialEq)]
+///
+/// Variants compare by name, `Native` is not equal to `Little` even
+/// on a little endian target, use `resolve()` to compare byte orders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Endian {
     /// Big endian.
     Big,
     /// Little endian.
     Little,
+    /// Endianness of the target platform.
+    Native,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for Endian {
     fn default() -> Self {
         Self::Big
     }
 }
 
+impl Endian {
+    /// Network byte order, which is big endian.
+    pub const NETWORK: Endian = Endian::Big;
+
+    /// Resolve `Native` to the endianness of the target platform.
+    pub fn resolve(self) -> Endian {
+        match self {
+            Endian::Native if cfg!(target_endian = "little") => Endian::Little,
+            Endian::Native => Endian::Big,
+            endian => endian,
+        }
+    }
+
+    /// Determine if the byte order is big endian.
+    pub fn is_big(self) -> bool {
+        self.resolve() == Endian::Big
+    }
+
+    /// Determine if the byte order is little endian.
+    pub fn is_little(self) -> bool {
+        self.resolve() == Endian::Little
+    }
+
+    /// Determine if the byte order is the endianness of the target platform.
+    pub fn is_native(self) -> bool {
+        self.resolve() == Endian::Native.resolve()
+    }
+
+    /// Get the opposite byte order.
+    pub fn swap(self) -> Endian {
+        if self.is_big() {
+            Endian::Little
+        } else {
+            Endian::Big
+        }
+    }
+}
+
 /// Trait for streams that can seek.
+#[allow(clippy::len_without_is_empty)]
 pub trait SeekStream {
     /// Seek to a position.
     fn seek(&mut self, to: usize) -> Result<usize>;
@@ -78,10 +165,33 @@ pub trait ReadStream: Read + SeekStream {}
 /// Trait for a writable stream.
 pub trait WriteStream: Write + SeekStream {}
 
+impl<S: SeekStream + ?Sized> SeekStream for &mut S {
+    fn seek(&mut self, to: usize) -> Result<usize> {
+        (**self).seek(to)
+    }
+
+    fn tell(&mut self) -> Result<usize> {
+        (**self).tell()
+    }
+
+    fn len(&self) -> Result<usize> {
+        (**self).len()
+    }
+}
+
+impl<S: ReadStream + ?Sized> ReadStream for &mut S {}
+impl<S: WriteStream + ?Sized> WriteStream for &mut S {}
+
 /// Read from a stream.
 pub struct BinaryReader<'a> {
     stream: &'a mut dyn ReadStream,
     endian: Endian,
+    base: usize,
+    positions: Vec<usize>,
+    path: Vec<context::Segment>,
+    limits: Limits,
+    allocated: usize,
+    tracer: Option<Box<trace::Tracer>>,
 }
 
 impl<'a> SeekStream for BinaryReader<'a> {
@@ -101,186 +211,237 @@ impl<'a> SeekStream for BinaryReader<'a> {
 impl<'a> BinaryReader<'a> {
     /// Create a binary reader with the given endianness.
     pub fn new(stream: &'a mut impl ReadStream, endian: Endian) -> Self {
-        Self { stream, endian }
+        Self {
+            stream,
+            endian,
+            base: 0,
+            positions: Vec::new(),
+            path: Vec::new(),
+            limits: Default::default(),
+            allocated: 0,
+            tracer: None,
+        }
     }
 
     /// Read a length-prefixed `String` from the stream.
     pub fn read_string(&mut self) -> Result<String> {
-        let chars = if cfg!(any(feature = "wasm32", feature = "string_len_u32")) {
-            let str_len = self.read_u32()?;
-            let mut chars: Vec<u8> = vec![0; str_len as usize];
-            self.stream.read_exact(&mut chars)?;
-            chars
-        } else {
-            let str_len = self.read_usize()?;
-            let mut chars: Vec<u8> = vec![0; str_len];
-            self.stream.read_exact(&mut chars)?;
-            chars
-        };
-        Ok(String::from_utf8(chars)?)
+        self.traced("read_string", |reader| {
+            let str_len = if cfg!(any(feature = "wasm32", feature = "string_len_u32")) {
+                reader.read_u32()? as usize
+            } else {
+                reader.read_usize()?
+            };
+            let chars = reader.read_bytes(str_len)?;
+            Ok(String::from_utf8(chars)?)
+        })
     }
 
     /// Read a 7bit encoded length-prefixed `String` from the stream.
     pub fn read_7bit_encoded_len_string(&mut self) -> Result<String> {
-        let chars = if cfg!(any(feature = "wasm32", feature = "string_len_u32")) {
-            let str_len = self.read_7bit_encoded_u32()?;
-            let mut chars: Vec<u8> = vec![0; str_len as usize];
-            self.stream.read_exact(&mut chars)?;
-            chars
-        } else {
-            let str_len = self.read_7bit_encoded_usize()?;
-            let mut chars: Vec<u8> = vec![0; str_len];
-            self.stream.read_exact(&mut chars)?;
-            chars
-        };
-        Ok(String::from_utf8(chars)?)
+        self.traced("read_7bit_encoded_len_string", |reader| {
+            let str_len = if cfg!(any(feature = "wasm32", feature = "string_len_u32")) {
+                reader.read_7bit_encoded_u32()? as usize
+            } else {
+                reader.read_7bit_encoded_usize()?
+            };
+            let chars = reader.read_bytes(str_len)?;
+            Ok(String::from_utf8(chars)?)
+        })
+    }
+
+    /// Get the endianness used to read values.
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    /// Set the endianness used to read values.
+    pub fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
     }
 
     /// Swap endianness to allow for reversing the reading mid stream
     pub fn swap_endianness(&mut self) {
-        if self.endian == Endian::Big {
-            self.endian = Endian::Little;
-        } else {
-            self.endian = Endian::Big;
-        }
+        self.endian = self.endian.swap();
     }
 
     /// Read a character from the stream.
     pub fn read_char(&mut self) -> Result<char> {
-        Ok(std::char::from_u32(self.read_u32()?).ok_or_else(|| BinaryError::InvalidChar)?)
+        self.traced("read_char", |reader| {
+            std::char::from_u32(reader.read_u32()?).ok_or(BinaryError::InvalidChar)
+        })
     }
 
     /// Read a `bool` from the stream.
     pub fn read_bool(&mut self) -> Result<bool> {
-        let value = self.read_u8()?;
-        Ok(value > 0)
+        self.traced("read_bool", |reader| {
+            let value = reader.read_u8()?;
+            Ok(value > 0)
+        })
     }
 
     /// Read a `f32` from the stream.
     pub fn read_f32(&mut self) -> Result<f32> {
-        let mut buffer: [u8; 4] = [0; 4];
-        self.stream.read_exact(&mut buffer)?;
-        decode!(self.endian, buffer, f32);
+        self.traced("read_f32", |reader| {
+            let mut buffer: [u8; 4] = [0; 4];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, f32);
+        })
     }
 
     /// Read a `f64` from the stream.
     pub fn read_f64(&mut self) -> Result<f64> {
-        let mut buffer: [u8; 8] = [0; 8];
-        self.stream.read_exact(&mut buffer)?;
-        decode!(self.endian, buffer, f64);
+        self.traced("read_f64", |reader| {
+            let mut buffer: [u8; 8] = [0; 8];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, f64);
+        })
     }
 
     /// Read an `isize` from the stream.
     #[cfg(target_arch = "wasm32")]
     pub fn read_isize(&mut self) -> Result<isize> {
-        let mut buffer: [u8; 4] = [0; 4];
-        self.stream.read(&mut buffer)?;
-        decode!(self.endian, buffer, isize);
+        self.traced("read_isize", |reader| {
+            let mut buffer: [u8; 4] = [0; 4];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, isize);
+        })
     }
 
     /// Read an `isize` from the stream.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn read_isize(&mut self) -> Result<isize> {
-        let mut buffer: [u8; 8] = [0; 8];
-        self.stream.read_exact(&mut buffer)?;
-        decode!(self.endian, buffer, isize);
+        self.traced("read_isize", |reader| {
+            let mut buffer: [u8; 8] = [0; 8];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, isize);
+        })
     }
 
     /// Read a `usize` from the stream.
     #[cfg(target_arch = "wasm32")]
     pub fn read_usize(&mut self) -> Result<usize> {
-        let mut buffer: [u8; 4] = [0; 4];
-        self.stream.read(&mut buffer)?;
-        decode!(self.endian, buffer, usize);
+        self.traced("read_usize", |reader| {
+            let mut buffer: [u8; 4] = [0; 4];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, usize);
+        })
     }
 
     /// Read a `usize` from the stream.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn read_usize(&mut self) -> Result<usize> {
-        let mut buffer: [u8; 8] = [0; 8];
-        self.stream.read_exact(&mut buffer)?;
-        decode!(self.endian, buffer, usize);
+        self.traced("read_usize", |reader| {
+            let mut buffer: [u8; 8] = [0; 8];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, usize);
+        })
     }
 
     /// Read a `u128` from the stream.
     pub fn read_u128(&mut self) -> Result<u128> {
-        let mut buffer: [u8; 16] = [0; 16];
-        self.stream.read_exact(&mut buffer)?;
-        decode!(self.endian, buffer, u128);
+        self.traced("read_u128", |reader| {
+            let mut buffer: [u8; 16] = [0; 16];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, u128);
+        })
     }
 
     /// Read an `i128` from the stream.
     pub fn read_i128(&mut self) -> Result<i128> {
-        let mut buffer: [u8; 16] = [0; 16];
-        self.stream.read_exact(&mut buffer)?;
-        decode!(self.endian, buffer, i128);
+        self.traced("read_i128", |reader| {
+            let mut buffer: [u8; 16] = [0; 16];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, i128);
+        })
     }
 
     /// Read a `u64` from the stream.
     pub fn read_u64(&mut self) -> Result<u64> {
-        let mut buffer: [u8; 8] = [0; 8];
-        self.stream.read_exact(&mut buffer)?;
-        decode!(self.endian, buffer, u64);
+        self.traced("read_u64", |reader| {
+            let mut buffer: [u8; 8] = [0; 8];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, u64);
+        })
     }
 
     /// Read an `i64` from the stream.
     pub fn read_i64(&mut self) -> Result<i64> {
-        let mut buffer: [u8; 8] = [0; 8];
-        self.stream.read_exact(&mut buffer)?;
-        decode!(self.endian, buffer, i64);
+        self.traced("read_i64", |reader| {
+            let mut buffer: [u8; 8] = [0; 8];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, i64);
+        })
     }
 
     /// Read a `u32` from the stream.
     pub fn read_u32(&mut self) -> Result<u32> {
-        let mut buffer: [u8; 4] = [0; 4];
-        self.stream.read_exact(&mut buffer)?;
-        decode!(self.endian, buffer, u32);
+        self.traced("read_u32", |reader| {
+            let mut buffer: [u8; 4] = [0; 4];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, u32);
+        })
     }
 
     /// Read an `i32` from the stream.
     pub fn read_i32(&mut self) -> Result<i32> {
-        let mut buffer: [u8; 4] = [0; 4];
-        self.stream.read_exact(&mut buffer)?;
-        decode!(self.endian, buffer, i32);
+        self.traced("read_i32", |reader| {
+            let mut buffer: [u8; 4] = [0; 4];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, i32);
+        })
     }
 
     /// Read a `u16` from the stream.
     pub fn read_u16(&mut self) -> Result<u16> {
-        let mut buffer: [u8; 2] = [0; 2];
-        self.stream.read_exact(&mut buffer)?;
-        decode!(self.endian, buffer, u16);
+        self.traced("read_u16", |reader| {
+            let mut buffer: [u8; 2] = [0; 2];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, u16);
+        })
     }
 
     /// Read an `i16` from the stream.
     pub fn read_i16(&mut self) -> Result<i16> {
-        let mut buffer: [u8; 2] = [0; 2];
-        self.stream.read_exact(&mut buffer)?;
-        decode!(self.endian, buffer, i16);
+        self.traced("read_i16", |reader| {
+            let mut buffer: [u8; 2] = [0; 2];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, i16);
+        })
     }
 
     /// Read a `u8` from the stream.
     pub fn read_u8(&mut self) -> Result<u8> {
-        let mut buffer: [u8; 1] = [0; 1];
-        self.stream.read_exact(&mut buffer)?;
-        decode!(self.endian, buffer, u8);
+        self.traced("read_u8", |reader| {
+            let mut buffer: [u8; 1] = [0; 1];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, u8);
+        })
     }
 
     /// Read an `i8` from the stream.
     pub fn read_i8(&mut self) -> Result<i8> {
-        let mut buffer: [u8; 1] = [0; 1];
-        self.stream.read_exact(&mut buffer)?;
-        decode!(self.endian, buffer, i8);
+        self.traced("read_i8", |reader| {
+            let mut buffer: [u8; 1] = [0; 1];
+            reader.fill(&mut buffer)?;
+            decode!(reader.endian, buffer, i8);
+        })
     }
 
-    /// Read 7bit encoded `usize` from the stream
-    pub fn read_7bit_encoded_usize(&mut self) -> Result<usize> {
-        let mut result: usize = 0;
-        let mut shift: usize = 0;
+    /// Read the bits of a 7bit encoded value.
+    ///
+    /// Fails once the value has more groups than fit in `bits` so a
+    /// corrupt stream cannot overflow the shift.
+    fn read_7bit_encoded(&mut self, bits: u32) -> Result<u128> {
+        let mut result: u128 = 0;
+        let mut shift: u32 = 0;
 
         loop {
+            if shift >= bits {
+                return Err(BinaryError::VarintTooLong { bits });
+            }
             let byte = self.read_u8()?;
 
-            result |= (byte as usize & 0x7F) << shift;
+            result |= (byte as u128 & 0x7F) << shift;
             shift += 7;
 
             if byte & 0x80 == 0 {
@@ -290,119 +451,63 @@ impl<'a> BinaryReader<'a> {
         Ok(result)
     }
 
+    /// Read 7bit encoded `usize` from the stream
+    pub fn read_7bit_encoded_usize(&mut self) -> Result<usize> {
+        self.traced("read_7bit_encoded_usize", |reader| {
+            Ok(reader.read_7bit_encoded(usize::BITS)? as usize)
+        })
+    }
+
     /// Read 7bit encoded `i64` from the stream
     pub fn read_7bit_encoded_i64(&mut self) -> Result<i64> {
-        let mut result: i64 = 0;
-        let mut shift: u64 = 0;
-
-        loop {
-            let byte = self.read_u8()?;
-
-            result |= (byte as i64 & 0x7F) << shift;
-            shift += 7;
-
-            if byte & 0x80 == 0 {
-                break;
-            }
-        }
-        Ok(result)
+        self.traced("read_7bit_encoded_i64", |reader| {
+            Ok(reader.read_7bit_encoded(i64::BITS)? as i64)
+        })
     }
 
     /// Read 7bit encoded `u128` from the stream
     pub fn read_7bit_encoded_u128(&mut self) -> Result<u128> {
-        let mut result: u128 = 0;
-        let mut shift: usize = 0;
-
-        loop {
-            let byte = self.read_u8()?;
-
-            result |= (byte as u128 & 0x7F) << shift;
-            shift += 7;
-
-            if byte & 0x80 == 0 {
-                break;
-            }
-        }
-        Ok(result)
+        self.traced("read_7bit_encoded_u128", |reader| {
+            reader.read_7bit_encoded(u128::BITS)
+        })
     }
 
     /// Read 7bit encoded `i128` from the stream
     pub fn read_7bit_encoded_i128(&mut self) -> Result<i128> {
-        let mut result: i128 = 0;
-        let mut shift: usize = 0;
-
-        loop {
-            let byte = self.read_u8()?;
-
-            result |= (byte as i128 & 0x7F) << shift;
-            shift += 7;
-
-            if byte & 0x80 == 0 {
-                break;
-            }
-        }
-        Ok(result)
+        self.traced("read_7bit_encoded_i128", |reader| {
+            Ok(reader.read_7bit_encoded(i128::BITS)? as i128)
+        })
     }
 
     /// Read 7bit encoded `u64` from the stream
     pub fn read_7bit_encoded_u64(&mut self) -> Result<u64> {
-        let mut result: u64 = 0;
-        let mut shift: usize = 0;
-
-        loop {
-            let byte = self.read_u8()?;
-
-            result |= (byte as u64 & 0x7F) << shift;
-            shift += 7;
-
-            if byte & 0x80 == 0 {
-                break;
-            }
-        }
-        Ok(result)
+        self.traced("read_7bit_encoded_u64", |reader| {
+            Ok(reader.read_7bit_encoded(u64::BITS)? as u64)
+        })
     }
 
     /// Read 7bit encoded `i32` from the stream
     pub fn read_7bit_encoded_i32(&mut self) -> Result<i32> {
-        let mut result: i32 = 0;
-        let mut shift: usize = 0;
-
-        loop {
-            let byte = self.read_u8()?;
-
-            result |= (byte as i32 & 0x7F) << shift;
-            shift += 7;
-
-            if byte & 0x80 == 0 {
-                break;
-            }
-        }
-        Ok(result)
+        self.traced("read_7bit_encoded_i32", |reader| {
+            Ok(reader.read_7bit_encoded(i32::BITS)? as i32)
+        })
     }
 
     /// Read 7bit encoded `u32` from the stream
     pub fn read_7bit_encoded_u32(&mut self) -> Result<u32> {
-        let mut result: u32 = 0;
-        let mut shift: usize = 0;
-
-        loop {
-            let byte = self.read_u8()?;
-
-            result |= (byte as u32 & 0x7F) << shift;
-            shift += 7;
-
-            if byte & 0x80 == 0 {
-                break;
-            }
-        }
-        Ok(result)
+        self.traced("read_7bit_encoded_u32", |reader| {
+            Ok(reader.read_7bit_encoded(u32::BITS)? as u32)
+        })
     }
 
     /// Read bytes from the stream into a buffer.
     pub fn read_bytes(&mut self, length: usize) -> Result<Vec<u8>> {
-        let mut buffer: Vec<u8> = vec![0; length];
-        self.stream.read_exact(&mut buffer)?;
-        Ok(buffer)
+        self.traced("read_bytes", |reader| {
+            reader.check_allocation(length)?;
+            let mut buffer: Vec<u8> = vec![0; length];
+            reader.fill(&mut buffer)?;
+            Ok(buffer)
+        })
     }
 }
 
@@ -410,6 +515,7 @@ impl<'a> BinaryReader<'a> {
 pub struct BinaryWriter<'a> {
     stream: &'a mut dyn WriteStream,
     endian: Endian,
+    labels: label::Labels,
 }
 
 impl<'a> SeekStream for BinaryWriter<'a> {
@@ -429,7 +535,11 @@ impl<'a> SeekStream for BinaryWriter<'a> {
 impl<'a> BinaryWriter<'a> {
     /// Create a binary writer with the given endianness.
     pub fn new(stream: &'a mut impl WriteStream, endian: Endian) -> Self {
-        Self { stream, endian }
+        Self {
+            stream,
+            endian,
+            labels: Default::default(),
+        }
     }
 
     /// Write a length-prefixed `String` to the stream.
@@ -444,7 +554,7 @@ impl<'a> BinaryWriter<'a> {
         } else {
             self.write_usize(bytes.len())?;
         }
-        Ok(self.stream.write(&bytes.to_vec())?)
+        Ok(self.stream.write(bytes)?)
     }
 
     /// Write a 7bit encoded length-prefixed `String` to the stream.
@@ -459,7 +569,7 @@ impl<'a> BinaryWriter<'a> {
         } else {
             self.write_7bit_encoded_usize(bytes.len())?;
         }
-        Ok(self.stream.write(&bytes.to_vec())?)
+        Ok(self.stream.write(bytes)?)
     }
 
     /// Write a character to the stream.
@@ -552,7 +662,7 @@ impl<'a> BinaryWriter<'a> {
     pub fn write_bytes_with_value(&mut self, count: usize, fill_value: u8) -> Result<usize> {
         let mut buff = Vec::with_capacity(count) as Vec<u8>;
         buff.resize(count, fill_value);
-        Ok(self.write_bytes(buff)?)
+        self.write_bytes(buff)
     }
 
     /// Write 7bit encoded i32 to the stream
@@ -626,24 +736,70 @@ impl<'a> BinaryWriter<'a> {
         Ok(length + 1)
     }
 
+    /// Get the endianness used to write values.
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    /// Set the endianness used to write values.
+    pub fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
+    }
+
     /// Swap endianness to allow for reversing the writing mid stream
     pub fn swap_endianness(&mut self) {
-        if self.endian == Endian::Big {
-            self.endian = Endian::Little;
-        } else {
-            self.endian = Endian::Big;
-        }
+        self.endian = self.endian.swap();
     }
 }
 
 /// Trait for encoding to binary.
+///
+/// With the `derive` feature `Encode` and `Decode` can be derived for
+/// structs, fields are encoded in order and the layout is described
+/// with `#[binary_rw(...)]` attributes that apply to both sides:
+///
+/// * `magic = b"RIFF"` on the struct or a field, bytes that are written
+///   before it and must match when decoding.
+/// * `pad_before = 4` on a field, zero bytes written before it and
+///   skipped when decoding.
+/// * `count = self.len as usize` on a `Vec` field, the number of
+///   elements. Encoding fails with `BinaryError::CountMismatch` if the
+///   vector has a different length.
+/// * `if = self.flags & 1 != 0` on a field, the field is only present
+///   when the condition holds, otherwise it decodes to its default.
+/// * `assert(self.version <= 2)` on a field, a condition checked before
+///   encoding and after decoding the field that fails with
+///   `BinaryError::AssertionFailed`.
+///
+/// Expressions are evaluated against `self` and can refer to the fields
+/// before the attributed field, which are already decoded.
+///
+/// Type parameters of a derived struct are bound by `Encode`, or by
+/// `Decode` and `Default` for the decoding side.
 pub trait Encode {
     /// Encode self into the binary writer.
     fn encode(&self, writer: &mut BinaryWriter) -> Result<()>;
 }
 
 /// Trait for decoding from binary.
+///
+/// Wrap nested reads in `BinaryReader::field()` and
+/// `BinaryReader::element()` so errors report which field failed.
 pub trait Decode {
     /// Decode from the binary reader into self.
     fn decode(&mut self, reader: &mut BinaryReader) -> Result<()>;
 }
+
+impl Encode for String {
+    fn encode(&self, writer: &mut BinaryWriter) -> Result<()> {
+        writer.write_string(self)?;
+        Ok(())
+    }
+}
+
+impl Decode for String {
+    fn decode(&mut self, reader: &mut BinaryReader) -> Result<()> {
+        *self = reader.read_string()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+//! Limits on allocations driven by lengths read from the stream.
+use crate::{BinaryError, BinaryReader, Result};
+
+/// Limits checked before the reader allocates memory.
+///
+/// Lengths read from the stream are untrusted, the limits stop a
+/// small hostile input from requesting an enormous allocation.
+/// The default limits are unbounded, set the fields to restrict them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum number of bytes in a single allocation.
+    pub max_allocation: usize,
+    /// Maximum number of bytes allocated in total until the
+    /// budget is reset with `BinaryReader::reset_allocated()`.
+    pub max_total_allocation: usize,
+    /// Maximum number of elements in a collection.
+    pub max_elements: usize,
+}
+
+impl Limits {
+    /// Create unbounded limits.
+    pub fn new() -> Self {
+        Self {
+            max_allocation: usize::MAX,
+            max_total_allocation: usize::MAX,
+            max_elements: usize::MAX,
+        }
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> BinaryReader<'a> {
+    /// Get the allocation limits.
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Set the allocation limits.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Get the number of bytes allocated against the total budget.
+    pub fn allocated(&self) -> usize {
+        self.allocated
+    }
+
+    /// Reset the total allocation budget.
+    ///
+    /// Call this between decodes when reusing a reader.
+    pub fn reset_allocated(&mut self) {
+        self.allocated = 0;
+    }
+
+    /// Check an allocation of `bytes` against the limits and charge
+    /// it to the total budget.
+    ///
+    /// The allocation must be filled from the stream; when the length
+    /// of the stream is known, requests for more bytes than remain
+    /// fail before anything is allocated.
+    pub fn check_allocation(&mut self, bytes: usize) -> Result<()> {
+        if bytes > self.limits.max_allocation {
+            return Err(BinaryError::AllocationLimit {
+                requested: bytes,
+                limit: self.limits.max_allocation,
+            });
+        }
+        let total = self.allocated.saturating_add(bytes);
+        if total > self.limits.max_total_allocation {
+            return Err(BinaryError::AllocationLimit {
+                requested: total,
+                limit: self.limits.max_total_allocation,
+            });
+        }
+        // Streams such as the decompression reader have no known length.
+        if let Ok(length) = self.stream.len() {
+            let offset = self.stream.tell()?;
+            let available = length.saturating_sub(offset);
+            if bytes > available {
+                return Err(BinaryError::ReadPastEof {
+                    offset,
+                    requested: bytes,
+                    available,
+                });
+            }
+        }
+        self.allocated = total;
+        Ok(())
+    }
+
+    /// Check the element count of a collection against the limits.
+    ///
+    /// Call this before reserving space for the elements.
+    pub fn check_elements(&self, count: usize) -> Result<()> {
+        if count > self.limits.max_elements {
+            return Err(BinaryError::ElementLimit {
+                count,
+                limit: self.limits.max_elements,
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+//! Magic numbers and signatures that must match a constant.
+use crate::{BinaryError, BinaryReader, BinaryWriter, Result};
+
+macro_rules! expect_int {
+    ($method:ident, $kind:ty) => {
+        #[doc = concat!("Read a `", stringify!($kind), "` and check it is equal to `value`.")]
+        ///
+        /// The value is compared as bytes in the endianness of the reader.
+        pub fn $method(&mut self, value: $kind) -> Result<()> {
+            let expected = if self.endian.is_little() {
+                value.to_le_bytes()
+            } else {
+                value.to_be_bytes()
+            };
+            self.expect_bytes(expected)
+        }
+    };
+}
+
+macro_rules! write_magic_int {
+    ($method:ident, $kind:ty, $write:ident, $expect:ident) => {
+        #[doc = concat!("Write a `", stringify!($kind), "` magic number, the counterpart of `BinaryReader::", stringify!($expect), "()`.")]
+        pub fn $method(&mut self, value: $kind) -> Result<usize> {
+            self.$write(value)
+        }
+    };
+}
+
+impl<'a> BinaryReader<'a> {
+    /// Read bytes and check they are equal to `expected`.
+    ///
+    /// Returns `BinaryError::UnexpectedBytes` with the offset and the
+    /// bytes that were found when they differ.
+    pub fn expect_bytes<B: AsRef<[u8]>>(&mut self, expected: B) -> Result<()> {
+        let expected = expected.as_ref();
+        self.traced("expect_bytes", |reader| {
+            let offset = reader.stream.tell()?;
+            let mut actual = vec![0; expected.len()];
+            reader.fill(&mut actual)?;
+            if actual != expected {
+                return Err(BinaryError::UnexpectedBytes {
+                    offset,
+                    expected: expected.to_vec(),
+                    actual,
+                });
+            }
+            Ok(actual)
+        })?;
+        Ok(())
+    }
+
+    expect_int!(expect_u16, u16);
+    expect_int!(expect_u32, u32);
+    expect_int!(expect_u64, u64);
+}
+
+impl<'a> BinaryWriter<'a> {
+    /// Write magic bytes, the counterpart of `BinaryReader::expect_bytes()`.
+    pub fn write_magic_bytes<B: AsRef<[u8]>>(&mut self, magic: B) -> Result<usize> {
+        self.stream.write_all(magic.as_ref())?;
+        Ok(magic.as_ref().len())
+    }
+
+    write_magic_int!(write_magic_u16, u16, write_u16, expect_u16);
+    write_magic_int!(write_magic_u32, u32, write_u32, expect_u32);
+    write_magic_int!(write_magic_u64, u64, write_u64, expect_u64);
+}
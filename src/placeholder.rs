@@ -0,0 +1,140 @@
+//! Placeholders for values that are backpatched after writing.
+use crate::{BinaryError, BinaryWriter, MemoryStream, Result};
+use std::marker::PhantomData;
+
+/// Trait for values that can be written into a reserved slot.
+pub trait Reservable: Copy {
+    /// Number of bytes the value occupies in the stream.
+    const SIZE: usize;
+
+    /// Write the value to the binary writer.
+    fn write_to(self, writer: &mut BinaryWriter) -> Result<usize>;
+}
+
+macro_rules! reservable {
+    ($kind:ty, $method:ident) => {
+        impl Reservable for $kind {
+            const SIZE: usize = std::mem::size_of::<$kind>();
+
+            fn write_to(self, writer: &mut BinaryWriter) -> Result<usize> {
+                writer.$method(self)
+            }
+        }
+    };
+}
+
+reservable!(u8, write_u8);
+reservable!(i8, write_i8);
+reservable!(u16, write_u16);
+reservable!(i16, write_i16);
+reservable!(u32, write_u32);
+reservable!(i32, write_i32);
+reservable!(u64, write_u64);
+reservable!(i64, write_i64);
+reservable!(u128, write_u128);
+reservable!(i128, write_i128);
+reservable!(usize, write_usize);
+reservable!(isize, write_isize);
+reservable!(f32, write_f32);
+reservable!(f64, write_f64);
+
+/// Slot reserved in a stream that is filled once the value is known.
+///
+/// Created by [`BinaryWriter::reserve`] and consumed by [`BinaryWriter::fill`].
+#[must_use = "a reserved slot is left zeroed unless it is filled"]
+#[derive(Debug)]
+pub struct Placeholder<T: Reservable> {
+    position: usize,
+    marker: PhantomData<T>,
+}
+
+impl<T: Reservable> Placeholder<T> {
+    /// Get the stream position of the reserved slot.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+/// Encodings for the length prefix of a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthPrefix {
+    /// Length is written as a `u16`.
+    U16,
+    /// Length is written as a `u32`.
+    U32,
+    /// Length is written as a `u64`.
+    U64,
+    /// Length is written as a 7bit encoded `usize`.
+    Varint,
+}
+
+impl<'a> BinaryWriter<'a> {
+    /// Reserve a zeroed slot for a value that is written later.
+    pub fn reserve<T: Reservable>(&mut self) -> Result<Placeholder<T>> {
+        let position = self.stream.tell()?;
+        self.write_bytes_with_value(T::SIZE, 0)?;
+        Ok(Placeholder {
+            position,
+            marker: PhantomData,
+        })
+    }
+
+    /// Write a value into a reserved slot.
+    ///
+    /// The stream position is restored after the value is written.
+    pub fn fill<T: Reservable>(&mut self, placeholder: Placeholder<T>, value: T) -> Result<()> {
+        let position = self.stream.tell()?;
+        self.stream.seek(placeholder.position)?;
+        value.write_to(self)?;
+        self.stream.seek(position)?;
+        Ok(())
+    }
+
+    /// Write a block preceded by its length in bytes.
+    ///
+    /// Fixed width prefixes are reserved up front and backpatched once
+    /// the block has been written, a `Varint` prefix buffers the block
+    /// in memory so stream positions inside `body` are relative to the
+    /// start of the block. Labels defined and referenced inside the
+    /// block are moved to this writer and resolved by `finish()`.
+    ///
+    /// Returns the number of bytes written including the prefix.
+    pub fn write_length_prefixed<F>(&mut self, prefix: LengthPrefix, body: F) -> Result<usize>
+    where
+        F: FnOnce(&mut BinaryWriter) -> Result<()>,
+    {
+        match prefix {
+            LengthPrefix::U16 => self.write_backpatched::<u16, F>(body),
+            LengthPrefix::U32 => self.write_backpatched::<u32, F>(body),
+            LengthPrefix::U64 => self.write_backpatched::<u64, F>(body),
+            LengthPrefix::Varint => {
+                let mut buffer = MemoryStream::new();
+                let mut writer = BinaryWriter::new(&mut buffer, self.endian);
+                body(&mut writer)?;
+                let labels = std::mem::take(&mut writer.labels);
+                let buffer: Vec<u8> = buffer.into();
+                let written = self.write_7bit_encoded_usize(buffer.len())?;
+                let base = self.stream.tell()?;
+                self.labels.merge(labels, base)?;
+                Ok(written + self.write_bytes(buffer)?)
+            }
+        }
+    }
+
+    fn write_backpatched<T, F>(&mut self, body: F) -> Result<usize>
+    where
+        T: Reservable + TryFrom<usize>,
+        BinaryError: From<T::Error>,
+        F: FnOnce(&mut BinaryWriter) -> Result<()>,
+    {
+        let placeholder = self.reserve::<T>()?;
+        let start = self.stream.tell()?;
+        body(self)?;
+        let end = self.stream.tell()?;
+        let length = end
+            .checked_sub(start)
+            .ok_or(BinaryError::BlockUnderflow { start, end })?;
+        self.fill(placeholder, T::try_from(length)?)?;
+        Ok(T::SIZE + length)
+    }
+}
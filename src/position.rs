@@ -0,0 +1,56 @@
+//! Scoped reads at offsets with automatic return.
+use crate::{BinaryError, BinaryReader, Result};
+
+impl<'a> BinaryReader<'a> {
+    /// Get the base that offsets passed to `at()` are relative to.
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    /// Set the base that offsets passed to `at()` are relative to.
+    ///
+    /// Useful when a format stores offsets relative to the start
+    /// of a table or an embedded file rather than the stream.
+    pub fn set_base(&mut self, base: usize) {
+        self.base = base;
+    }
+
+    /// Save the current stream position.
+    pub fn push_position(&mut self) -> Result<()> {
+        let position = self.stream.tell()?;
+        self.positions.push(position);
+        Ok(())
+    }
+
+    /// Seek back to the most recently saved stream position.
+    pub fn pop_position(&mut self) -> Result<usize> {
+        let position = self
+            .positions
+            .pop()
+            .ok_or(BinaryError::PositionStackEmpty)?;
+        self.stream.seek(position)
+    }
+
+    /// Read from an offset relative to the base and return to
+    /// the current position afterwards.
+    ///
+    /// The position is restored even when `read` returns an error.
+    pub fn at<T, F>(&mut self, offset: usize, read: F) -> Result<T>
+    where
+        F: FnOnce(&mut BinaryReader) -> Result<T>,
+    {
+        let target = self
+            .base
+            .checked_add(offset)
+            .ok_or(BinaryError::OffsetOverflow {
+                base: self.base,
+                offset,
+            })?;
+        let position = self.stream.tell()?;
+        let result = self.stream.seek(target).and_then(|_| read(self));
+        let restored = self.stream.seek(position);
+        let value = result?;
+        restored?;
+        Ok(value)
+    }
+}
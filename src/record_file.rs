@@ -0,0 +1,198 @@
+//! File of fixed-size records with random access by index.
+use crate::{
+    BinaryError, BinaryReader, BinaryWriter, Decode, Encode, Endian, FileStream, MemoryStream,
+    Result, SeekStream,
+};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// Trait for records that always encode to the same number of bytes.
+pub trait FixedSize: Encode + Decode + Default {
+    /// Number of bytes in the encoded record.
+    const SIZE: usize;
+}
+
+/// File holding a header followed by records of the same size.
+///
+/// Record `index` is stored at `header_size + index * T::SIZE`.
+/// Records that encode or decode to a different number of bytes
+/// than `T::SIZE` fail with `BinaryError::RecordSize`.
+pub struct RecordFile<T: FixedSize> {
+    stream: FileStream,
+    endian: Endian,
+    header_size: usize,
+    len: usize,
+    marker: PhantomData<T>,
+}
+
+impl<T: FixedSize> RecordFile<T> {
+    /// Create a record file with a header and no records.
+    ///
+    /// If the file exists it is truncated.
+    pub fn create<P: AsRef<Path>>(path: P, endian: Endian, header: &[u8]) -> Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        let mut stream = FileStream::new(file)?;
+        stream.write_all(header)?;
+        RecordFile::new(stream, endian, header.len())
+    }
+
+    /// Open an existing record file with a header of `header_size` bytes.
+    pub fn open<P: AsRef<Path>>(path: P, endian: Endian, header_size: usize) -> Result<Self> {
+        RecordFile::new(FileStream::write(path)?, endian, header_size)
+    }
+
+    /// Create a record file from a stream.
+    ///
+    /// The length of the stream must be a header of `header_size` bytes
+    /// followed by a whole number of records.
+    pub fn new(stream: FileStream, endian: Endian, header_size: usize) -> Result<Self> {
+        let length = stream.len()?;
+        if T::SIZE == 0 || length < header_size || !(length - header_size).is_multiple_of(T::SIZE) {
+            return Err(BinaryError::InvalidRecordFile {
+                length,
+                header: header_size,
+                record: T::SIZE,
+            });
+        }
+        Ok(Self {
+            stream,
+            endian,
+            header_size,
+            len: (length - header_size) / T::SIZE,
+            marker: PhantomData,
+        })
+    }
+
+    /// Number of records in the file.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Determine if the file has no records.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Read the header.
+    pub fn header(&mut self) -> Result<Vec<u8>> {
+        self.stream.seek(0)?;
+        let endian = self.endian;
+        BinaryReader::new(&mut self.stream, endian).read_bytes(self.header_size)
+    }
+
+    /// Read the record at `index`.
+    pub fn get(&mut self, index: usize) -> Result<T> {
+        self.check_index(index)?;
+        let offset = self.offset(index);
+        self.stream.seek(offset)?;
+        let endian = self.endian;
+        let mut reader = BinaryReader::new(&mut self.stream, endian);
+        let mut value = T::default();
+        value.decode(&mut reader)?;
+        let actual = reader.tell()? - offset;
+        if actual != T::SIZE {
+            return Err(BinaryError::RecordSize {
+                expected: T::SIZE,
+                actual,
+            });
+        }
+        Ok(value)
+    }
+
+    /// Overwrite the record at `index`.
+    pub fn set(&mut self, index: usize, value: &T) -> Result<()> {
+        self.check_index(index)?;
+        self.write_record(index, value)
+    }
+
+    /// Append a record and return its index.
+    pub fn push(&mut self, value: &T) -> Result<usize> {
+        let index = self.len;
+        self.write_record(index, value)?;
+        self.len += 1;
+        Ok(index)
+    }
+
+    /// Iterate the records from the start of the file.
+    pub fn iter(&mut self) -> RecordFileIter<'_, T> {
+        RecordFileIter {
+            file: self,
+            index: 0,
+        }
+    }
+
+    /// Write buffered records and wait for them to reach the disk.
+    pub fn sync(&mut self) -> Result<()> {
+        self.stream.sync_data()
+    }
+
+    /// Consume the record file and return the stream.
+    pub fn into_inner(self) -> FileStream {
+        self.stream
+    }
+
+    fn offset(&self, index: usize) -> usize {
+        self.header_size + index * T::SIZE
+    }
+
+    fn check_index(&self, index: usize) -> Result<()> {
+        if index >= self.len {
+            return Err(BinaryError::IndexOutOfBounds {
+                index,
+                len: self.len,
+            });
+        }
+        Ok(())
+    }
+
+    /// Encode a record and write it at `index`.
+    ///
+    /// The record is encoded into a buffer first so a record of the
+    /// wrong size does not overwrite its neighbours.
+    fn write_record(&mut self, index: usize, value: &T) -> Result<()> {
+        let endian = self.endian;
+        let mut buffer = MemoryStream::with_capacity(T::SIZE);
+        value.encode(&mut BinaryWriter::new(&mut buffer, endian))?;
+        if buffer.as_slice().len() != T::SIZE {
+            return Err(BinaryError::RecordSize {
+                expected: T::SIZE,
+                actual: buffer.as_slice().len(),
+            });
+        }
+        let offset = self.offset(index);
+        self.stream.seek(offset)?;
+        self.stream.write_all(buffer.as_slice())?;
+        Ok(())
+    }
+}
+
+/// Iterator over the records of a record file.
+pub struct RecordFileIter<'a, T: FixedSize> {
+    file: &'a mut RecordFile<T>,
+    index: usize,
+}
+
+impl<'a, T: FixedSize> Iterator for RecordFileIter<'a, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.file.len {
+            return None;
+        }
+        let value = self.file.get(self.index);
+        self.index += 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.file.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
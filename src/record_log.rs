@@ -0,0 +1,252 @@
+//! Append-only log of records in a file.
+use crate::{
+    BinaryError, BinaryReader, BinaryWriter, Checksum, Crc32, Decode, Encode, Endian, FileStream,
+    MemoryStream, Result, SeekStream, SliceStream,
+};
+use std::fs::OpenOptions;
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// Size of the length and checksums before each record.
+const HEADER_SIZE: usize = 12;
+
+/// When a record log waits for appended records to reach the disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncPolicy {
+    /// Records reach the disk when `RecordLog::sync()` is called.
+    #[default]
+    Manual,
+    /// Every append waits for the record to reach the disk.
+    Always,
+}
+
+/// Append-only log of records stored in a file.
+///
+/// Each record is a `u32` payload length, the CRC-32 of the payload and
+/// the CRC-32 of the length and payload checksum followed by the payload.
+/// Opening a log checks every record, a final record that is incomplete
+/// or fails its checksums is treated as a write torn by a crash and
+/// truncated, any other record that fails its checksums is reported as
+/// `BinaryError::CorruptRecord`. The header has its own checksum so a
+/// corrupt length is not mistaken for a final record.
+pub struct RecordLog {
+    stream: FileStream,
+    endian: Endian,
+    sync: SyncPolicy,
+    truncated: usize,
+}
+
+impl RecordLog {
+    /// Open a log file, creating it if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P, endian: Endian) -> Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        RecordLog::new(FileStream::new(file)?, endian)
+    }
+
+    /// Create a log from a stream opened for reading and writing.
+    ///
+    /// The records are checked and a torn final record is truncated.
+    pub fn new(stream: FileStream, endian: Endian) -> Result<Self> {
+        let mut log = Self {
+            stream,
+            endian,
+            sync: Default::default(),
+            truncated: 0,
+        };
+        let length = log.stream.len()?;
+        let mut offset = 0;
+        while offset < length {
+            match log.read_record(offset)? {
+                Record::Valid(payload) => offset += HEADER_SIZE + payload.len(),
+                Record::CorruptPayload(size) if offset + HEADER_SIZE + size < length => {
+                    return Err(BinaryError::CorruptRecord { offset });
+                }
+                Record::CorruptHeader if offset + HEADER_SIZE < length => {
+                    return Err(BinaryError::CorruptRecord { offset });
+                }
+                _ => {
+                    log.stream.set_len(offset)?;
+                    log.truncated = length - offset;
+                    break;
+                }
+            }
+        }
+        Ok(log)
+    }
+
+    /// Number of bytes truncated from a torn final record when the
+    /// log was opened.
+    pub fn truncated(&self) -> usize {
+        self.truncated
+    }
+
+    /// Offset of the end of the log where the next record is appended.
+    pub fn end(&self) -> Result<usize> {
+        self.stream.len()
+    }
+
+    /// Set when appended records wait to reach the disk.
+    pub fn set_sync_policy(&mut self, sync: SyncPolicy) {
+        self.sync = sync;
+    }
+
+    /// Get when appended records wait to reach the disk.
+    pub fn sync_policy(&self) -> SyncPolicy {
+        self.sync
+    }
+
+    /// Wait for all appended records to reach the disk.
+    pub fn sync(&mut self) -> Result<()> {
+        self.stream.sync_data()
+    }
+
+    /// Encode a value and append it as a record.
+    ///
+    /// Returns the offset of the record.
+    pub fn append<T: Encode + ?Sized>(&mut self, value: &T) -> Result<usize> {
+        let mut buffer = MemoryStream::new();
+        value.encode(&mut BinaryWriter::new(&mut buffer, self.endian))?;
+        self.append_bytes(buffer.as_slice())
+    }
+
+    /// Append a payload as a record.
+    ///
+    /// Returns the offset of the record.
+    pub fn append_bytes<B: AsRef<[u8]>>(&mut self, payload: B) -> Result<usize> {
+        let payload = payload.as_ref();
+        let mut checksum = Crc32::new();
+        checksum.update(payload);
+
+        let mut header = MemoryStream::with_capacity(HEADER_SIZE);
+        let mut writer = BinaryWriter::new(&mut header, self.endian);
+        writer.write_u32(u32::try_from(payload.len())?)?;
+        writer.write_u32(checksum.digest() as u32)?;
+        let mut checksum = Crc32::new();
+        checksum.update(header.as_slice());
+        BinaryWriter::new(&mut header, self.endian).write_u32(checksum.digest() as u32)?;
+
+        let offset = self.stream.len()?;
+        self.stream.seek(offset)?;
+        let endian = self.endian;
+        let mut writer = BinaryWriter::new(&mut self.stream, endian);
+        writer.write_bytes(header.as_slice())?;
+        writer.write_bytes(payload)?;
+        if self.sync == SyncPolicy::Always {
+            self.sync()?;
+        }
+        Ok(offset)
+    }
+
+    /// Iterate the records from the start of the log.
+    pub fn iter<T: Decode + Default>(&mut self) -> Records<'_, T> {
+        self.iter_from(0)
+    }
+
+    /// Iterate the records starting at the record at `offset`.
+    ///
+    /// Use `Records::offset()` to find where to resume iterating.
+    pub fn iter_from<T: Decode + Default>(&mut self, offset: usize) -> Records<'_, T> {
+        Records {
+            log: self,
+            offset,
+            marker: PhantomData,
+        }
+    }
+
+    /// Read and check the record at `offset`.
+    fn read_record(&mut self, offset: usize) -> Result<Record> {
+        let length = self.stream.len()?;
+        if length.saturating_sub(offset) < HEADER_SIZE {
+            return Ok(Record::Incomplete);
+        }
+        self.stream.seek(offset)?;
+        let endian = self.endian;
+        let mut reader = BinaryReader::new(&mut self.stream, endian);
+        let header = reader.read_bytes(HEADER_SIZE - 4)?;
+        let expected = reader.read_u32()?;
+        let mut checksum = Crc32::new();
+        checksum.update(&header);
+        if checksum.digest() as u32 != expected {
+            return Ok(Record::CorruptHeader);
+        }
+
+        let mut stream = SliceStream::new(&header);
+        let mut header = BinaryReader::new(&mut stream, endian);
+        let size = usize::try_from(header.read_u32()?)?;
+        let expected = header.read_u32()?;
+        if size > length - offset - HEADER_SIZE {
+            return Ok(Record::Incomplete);
+        }
+        let payload = reader.read_bytes(size)?;
+        let mut checksum = Crc32::new();
+        checksum.update(&payload);
+        if checksum.digest() as u32 != expected {
+            return Ok(Record::CorruptPayload(size));
+        }
+        Ok(Record::Valid(payload))
+    }
+}
+
+/// Record read from a log.
+enum Record {
+    /// The header and payload match their checksums.
+    Valid(Vec<u8>),
+    /// The header matches its checksum but the payload of this
+    /// many bytes does not.
+    CorruptPayload(usize),
+    /// The header does not match its checksum.
+    CorruptHeader,
+    /// The header or payload extends past the end of the file.
+    Incomplete,
+}
+
+/// Iterator over the records of a log.
+///
+/// Iteration stops after the first error.
+pub struct Records<'a, T> {
+    log: &'a mut RecordLog,
+    offset: usize,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T> Records<'a, T> {
+    /// Offset of the next record.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a, T: Decode + Default> Iterator for Records<'a, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let end = match self.log.end() {
+            Ok(end) => end,
+            Err(error) => return Some(Err(error)),
+        };
+        if self.offset >= end {
+            return None;
+        }
+        let offset = self.offset;
+        // Stop on errors by moving to the end of the log.
+        self.offset = end;
+        let payload = match self.log.read_record(offset) {
+            Ok(Record::Valid(payload)) => payload,
+            Ok(_) => return Some(Err(BinaryError::CorruptRecord { offset })),
+            Err(error) => return Some(Err(error)),
+        };
+        let mut stream = SliceStream::new(&payload);
+        let mut reader = BinaryReader::new(&mut stream, self.log.endian);
+        let mut value = T::default();
+        if let Err(error) = value.decode(&mut reader) {
+            return Some(Err(error));
+        }
+        self.offset = offset + HEADER_SIZE + payload.len();
+        Some(Ok(value))
+    }
+}
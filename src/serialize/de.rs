@@ -0,0 +1,283 @@
+//! Deserializer reading from a binary reader.
+use super::LengthEncoding;
+use crate::{BinaryError, BinaryReader, Result};
+use serde::de::{
+    self, value::U32Deserializer, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+
+/// Serde deserializer that reads values from a binary reader.
+///
+/// Lengths read from the stream are checked against the limits of
+/// the reader and errors are annotated with the path to the struct
+/// field or sequence element being decoded.
+pub struct Deserializer<'r, 'a> {
+    reader: &'r mut BinaryReader<'a>,
+    lengths: LengthEncoding,
+}
+
+impl<'r, 'a> Deserializer<'r, 'a> {
+    /// Create a deserializer reading from a binary reader.
+    pub fn new(reader: &'r mut BinaryReader<'a>, lengths: LengthEncoding) -> Self {
+        Self { reader, lengths }
+    }
+
+    fn read_length(&mut self) -> Result<usize> {
+        match self.lengths {
+            LengthEncoding::Fixed => self.reader.read_usize(),
+            LengthEncoding::Varint => self.reader.read_7bit_encoded_usize(),
+        }
+    }
+
+    fn read_variant(&mut self) -> Result<u32> {
+        match self.lengths {
+            LengthEncoding::Fixed => self.reader.read_u32(),
+            LengthEncoding::Varint => self.reader.read_7bit_encoded_u32(),
+        }
+    }
+
+    fn read_collection_length(&mut self) -> Result<usize> {
+        let length = self.read_length()?;
+        self.reader.check_elements(length)?;
+        Ok(length)
+    }
+}
+
+macro_rules! deserialize {
+    ($method:ident, $visit:ident, $read:ident) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+            visitor.$visit(self.reader.$read()?)
+        }
+    };
+}
+
+impl<'de, 'd, 'r, 'a> de::Deserializer<'de> for &'d mut Deserializer<'r, 'a> {
+    type Error = BinaryError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(BinaryError::Unsupported("deserialize_any"))
+    }
+
+    deserialize!(deserialize_bool, visit_bool, read_bool);
+    deserialize!(deserialize_i8, visit_i8, read_i8);
+    deserialize!(deserialize_i16, visit_i16, read_i16);
+    deserialize!(deserialize_i32, visit_i32, read_i32);
+    deserialize!(deserialize_i64, visit_i64, read_i64);
+    deserialize!(deserialize_i128, visit_i128, read_i128);
+    deserialize!(deserialize_u8, visit_u8, read_u8);
+    deserialize!(deserialize_u16, visit_u16, read_u16);
+    deserialize!(deserialize_u32, visit_u32, read_u32);
+    deserialize!(deserialize_u64, visit_u64, read_u64);
+    deserialize!(deserialize_u128, visit_u128, read_u128);
+    deserialize!(deserialize_f32, visit_f32, read_f32);
+    deserialize!(deserialize_f64, visit_f64, read_f64);
+    deserialize!(deserialize_char, visit_char, read_char);
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let value = match self.lengths {
+            LengthEncoding::Fixed => self.reader.read_string()?,
+            LengthEncoding::Varint => self.reader.read_7bit_encoded_len_string()?,
+        };
+        visitor.visit_string(value)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let length = self.read_length()?;
+        visitor.visit_byte_buf(self.reader.read_bytes(length)?)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.reader.read_bool()? {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let length = self.read_collection_length()?;
+        visitor.visit_seq(Access::new(self, length, None))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, length: usize, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(Access::new(self, length, None))
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        length: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_seq(Access::new(self, length, None))
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let length = self.read_collection_length()?;
+        visitor.visit_map(Access::new(self, length, None))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_seq(Access::new(self, fields.len(), Some(fields)))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(BinaryError::Unsupported("deserialize_identifier"))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(BinaryError::Unsupported("deserialize_ignored_any"))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Access to the elements of a sequence, tuple, struct or map.
+struct Access<'d, 'r, 'a> {
+    deserializer: &'d mut Deserializer<'r, 'a>,
+    length: usize,
+    index: usize,
+    fields: Option<&'static [&'static str]>,
+}
+
+impl<'d, 'r, 'a> Access<'d, 'r, 'a> {
+    fn new(
+        deserializer: &'d mut Deserializer<'r, 'a>,
+        length: usize,
+        fields: Option<&'static [&'static str]>,
+    ) -> Self {
+        Self {
+            deserializer,
+            length,
+            index: 0,
+            fields,
+        }
+    }
+
+    /// Deserialize the next element within the scope of its
+    /// field name or index.
+    fn next<'de, T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value> {
+        let lengths = self.deserializer.lengths;
+        let index = self.index;
+        self.index += 1;
+        let read =
+            |reader: &mut BinaryReader| seed.deserialize(&mut Deserializer::new(reader, lengths));
+        match self.fields {
+            Some(fields) => self.deserializer.reader.field(fields[index], read),
+            None => self.deserializer.reader.element(index, read),
+        }
+    }
+}
+
+impl<'de, 'd, 'r, 'a> SeqAccess<'de> for Access<'d, 'r, 'a> {
+    type Error = BinaryError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        if self.index == self.length {
+            return Ok(None);
+        }
+        self.next(seed).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.length - self.index)
+    }
+}
+
+impl<'de, 'd, 'r, 'a> MapAccess<'de> for Access<'d, 'r, 'a> {
+    type Error = BinaryError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        if self.index == self.length {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        self.next(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.length - self.index)
+    }
+}
+
+impl<'de, 'd, 'r, 'a> EnumAccess<'de> for &'d mut Deserializer<'r, 'a> {
+    type Error = BinaryError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
+        let index = self.read_variant()?;
+        let variant = seed.deserialize(U32Deserializer::<BinaryError>::new(index))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, 'd, 'r, 'a> VariantAccess<'de> for &'d mut Deserializer<'r, 'a> {
+    type Error = BinaryError;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, length: usize, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(Access::new(self, length, None))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_seq(Access::new(self, fields.len(), Some(fields)))
+    }
+}
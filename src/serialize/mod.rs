@@ -0,0 +1,72 @@
+//! Serde serializer and deserializer backed by the binary reader and writer.
+//!
+//! The format is not self-describing, values are written in the order
+//! they are visited using the primitive encodings of the writer:
+//!
+//! * Structs and tuples are written field by field without a length.
+//! * Sequences, maps, strings and byte arrays are prefixed with their length.
+//! * Enums are prefixed with the index of the variant.
+//! * Options are prefixed with a `bool`.
+//!
+//! Lengths and variant indices are encoded using the selected
+//! [`LengthEncoding`], strings use the matching string mode of the
+//! writer so they can be read with `read_string()` or
+//! `read_7bit_encoded_len_string()`.
+use crate::{BinaryError, BinaryReader, BinaryWriter, Endian, MemoryStream, Result, SliceStream};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Display;
+
+mod de;
+mod ser;
+
+pub use de::Deserializer;
+pub use ser::Serializer;
+
+/// Encodings for lengths and enum variant indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LengthEncoding {
+    /// Lengths are written as `usize` and variant indices as `u32`,
+    /// strings are written with `write_string()`.
+    #[default]
+    Fixed,
+    /// Lengths and variant indices are 7bit encoded, strings are
+    /// written with `write_7bit_encoded_len_string()`.
+    Varint,
+}
+
+impl serde::ser::Error for BinaryError {
+    fn custom<T: Display>(message: T) -> Self {
+        BinaryError::Serde(message.to_string())
+    }
+}
+
+impl serde::de::Error for BinaryError {
+    fn custom<T: Display>(message: T) -> Self {
+        BinaryError::Serde(message.to_string())
+    }
+}
+
+/// Serialize a value into a vector of bytes.
+pub fn to_vec<T: Serialize + ?Sized>(
+    value: &T,
+    endian: Endian,
+    lengths: LengthEncoding,
+) -> Result<Vec<u8>> {
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, endian);
+    value.serialize(&mut Serializer::new(&mut writer, lengths))?;
+    Ok(stream.into())
+}
+
+/// Deserialize a value from a slice of bytes.
+///
+/// Bytes after the end of the value are ignored.
+pub fn from_slice<T: DeserializeOwned>(
+    bytes: &[u8],
+    endian: Endian,
+    lengths: LengthEncoding,
+) -> Result<T> {
+    let mut stream = SliceStream::new(bytes);
+    let mut reader = BinaryReader::new(&mut stream, endian);
+    T::deserialize(&mut Deserializer::new(&mut reader, lengths))
+}
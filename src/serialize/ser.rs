@@ -0,0 +1,245 @@
+//! Serializer writing to a binary writer.
+use super::LengthEncoding;
+use crate::{BinaryError, BinaryWriter, Result};
+use serde::ser::{
+    self, Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+};
+
+/// Serde serializer that writes values to a binary writer.
+pub struct Serializer<'w, 'a> {
+    writer: &'w mut BinaryWriter<'a>,
+    lengths: LengthEncoding,
+}
+
+impl<'w, 'a> Serializer<'w, 'a> {
+    /// Create a serializer writing to a binary writer.
+    pub fn new(writer: &'w mut BinaryWriter<'a>, lengths: LengthEncoding) -> Self {
+        Self { writer, lengths }
+    }
+
+    fn write_length(&mut self, length: usize) -> Result<()> {
+        match self.lengths {
+            LengthEncoding::Fixed => self.writer.write_usize(length)?,
+            LengthEncoding::Varint => self.writer.write_7bit_encoded_usize(length)?,
+        };
+        Ok(())
+    }
+
+    fn write_variant(&mut self, index: u32) -> Result<()> {
+        match self.lengths {
+            LengthEncoding::Fixed => self.writer.write_u32(index)?,
+            LengthEncoding::Varint => self.writer.write_7bit_encoded_u32(index)?,
+        };
+        Ok(())
+    }
+}
+
+macro_rules! serialize {
+    ($method:ident, $kind:ty, $write:ident) => {
+        fn $method(self, value: $kind) -> Result<()> {
+            self.writer.$write(value)?;
+            Ok(())
+        }
+    };
+}
+
+impl<'s, 'w, 'a> ser::Serializer for &'s mut Serializer<'w, 'a> {
+    type Ok = ();
+    type Error = BinaryError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    serialize!(serialize_bool, bool, write_bool);
+    serialize!(serialize_i8, i8, write_i8);
+    serialize!(serialize_i16, i16, write_i16);
+    serialize!(serialize_i32, i32, write_i32);
+    serialize!(serialize_i64, i64, write_i64);
+    serialize!(serialize_i128, i128, write_i128);
+    serialize!(serialize_u8, u8, write_u8);
+    serialize!(serialize_u16, u16, write_u16);
+    serialize!(serialize_u32, u32, write_u32);
+    serialize!(serialize_u64, u64, write_u64);
+    serialize!(serialize_u128, u128, write_u128);
+    serialize!(serialize_f32, f32, write_f32);
+    serialize!(serialize_f64, f64, write_f64);
+    serialize!(serialize_char, char, write_char);
+
+    fn serialize_str(self, value: &str) -> Result<()> {
+        match self.lengths {
+            LengthEncoding::Fixed => self.writer.write_string(value)?,
+            LengthEncoding::Varint => self.writer.write_7bit_encoded_len_string(value)?,
+        };
+        Ok(())
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<()> {
+        self.write_length(value.len())?;
+        self.writer.write_bytes(value)?;
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        self.writer.write_bool(false)?;
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<()> {
+        self.writer.write_bool(true)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+    ) -> Result<()> {
+        self.write_variant(index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.write_variant(index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, length: Option<usize>) -> Result<Self> {
+        let length = length.ok_or(BinaryError::Unsupported("sequence of unknown length"))?;
+        self.write_length(length)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _length: usize) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _length: usize) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        _length: usize,
+    ) -> Result<Self> {
+        self.write_variant(index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, length: Option<usize>) -> Result<Self> {
+        let length = length.ok_or(BinaryError::Unsupported("map of unknown length"))?;
+        self.write_length(length)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _length: usize) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        _length: usize,
+    ) -> Result<Self> {
+        self.write_variant(index)?;
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+macro_rules! compound {
+    ($trait:ident, $method:ident) => {
+        impl<'s, 'w, 'a> $trait for &'s mut Serializer<'w, 'a> {
+            type Ok = ();
+            type Error = BinaryError;
+
+            fn $method<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+                value.serialize(&mut **self)
+            }
+
+            fn end(self) -> Result<()> {
+                Ok(())
+            }
+        }
+    };
+}
+
+compound!(SerializeSeq, serialize_element);
+compound!(SerializeTuple, serialize_element);
+compound!(SerializeTupleStruct, serialize_field);
+compound!(SerializeTupleVariant, serialize_field);
+
+impl<'s, 'w, 'a> SerializeMap for &'s mut Serializer<'w, 'a> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+macro_rules! compound_struct {
+    ($trait:ident) => {
+        impl<'s, 'w, 'a> $trait for &'s mut Serializer<'w, 'a> {
+            type Ok = ();
+            type Error = BinaryError;
+
+            fn serialize_field<T: Serialize + ?Sized>(
+                &mut self,
+                _key: &'static str,
+                value: &T,
+            ) -> Result<()> {
+                value.serialize(&mut **self)
+            }
+
+            fn end(self) -> Result<()> {
+                Ok(())
+            }
+        }
+    };
+}
+
+compound_struct!(SerializeStruct);
+compound_struct!(SerializeStructVariant);
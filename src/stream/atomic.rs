@@ -0,0 +1,156 @@
+//! Stream that replaces a file atomically when committed.
+use crate::{FileStream, ReadStream, Result, SeekStream, WriteStream};
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counter that keeps temporary file names unique within a process.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Stream that writes a file atomically.
+///
+/// Writes go to a temporary file in the same directory as the target.
+/// `commit()` waits for the data to reach the disk and renames the
+/// temporary file over the target, so after a crash the target holds
+/// either its previous contents or everything that was written.
+/// Dropping the stream without committing removes the temporary file
+/// and leaves the target untouched.
+///
+/// When the target exists its permissions are copied to the new file.
+pub struct AtomicFile {
+    stream: Option<FileStream>,
+    path: PathBuf,
+    temp_path: PathBuf,
+    committed: bool,
+}
+
+impl AtomicFile {
+    /// Create a stream that replaces the file at `path` when committed.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let name = path
+            .file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy();
+        loop {
+            let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+            let temp_path =
+                path.with_file_name(format!(".{}.{}.{}.tmp", name, std::process::id(), counter));
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&temp_path);
+            match file {
+                Ok(file) => {
+                    return Ok(Self {
+                        stream: Some(FileStream::new(file)?),
+                        path,
+                        temp_path,
+                        committed: false,
+                    })
+                }
+                Err(error) if error.kind() == ErrorKind::AlreadyExists => continue,
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+
+    /// Buffer reads and writes using buffers of the given capacity.
+    pub fn buffered(mut self, capacity: usize) -> Self {
+        self.stream = self.stream.take().map(|stream| stream.buffered(capacity));
+        self
+    }
+
+    /// Get the path of the file that is replaced on commit.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the path of the temporary file being written.
+    pub fn temp_path(&self) -> &Path {
+        &self.temp_path
+    }
+
+    /// Wait for the written data to reach the disk and rename the
+    /// temporary file over the target.
+    ///
+    /// If the data cannot be written or renamed the temporary file is
+    /// removed and the target is left untouched.
+    pub fn commit(mut self) -> Result<()> {
+        let mut stream = self.stream.take().expect("stream is present until commit");
+        match std::fs::metadata(&self.path) {
+            Ok(metadata) => std::fs::set_permissions(&self.temp_path, metadata.permissions())?,
+            Err(error) if error.kind() == ErrorKind::NotFound => {}
+            Err(error) => return Err(error.into()),
+        }
+        stream.sync_all()?;
+        drop(stream);
+        std::fs::rename(&self.temp_path, &self.path)?;
+        self.committed = true;
+        // The rename is durable once the directory reaches the disk.
+        #[cfg(unix)]
+        if let Some(parent) = self.path.parent() {
+            let parent = if parent.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                parent
+            };
+            std::fs::File::open(parent)?.sync_all()?;
+        }
+        Ok(())
+    }
+
+    fn stream(&mut self) -> &mut FileStream {
+        self.stream
+            .as_mut()
+            .expect("stream is present until commit")
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        // Close the file before removing it.
+        self.stream.take();
+        if !self.committed {
+            let _ = std::fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+impl SeekStream for AtomicFile {
+    fn seek(&mut self, to: usize) -> Result<usize> {
+        self.stream().seek(to)
+    }
+
+    fn tell(&mut self) -> Result<usize> {
+        self.stream().tell()
+    }
+
+    fn len(&self) -> Result<usize> {
+        self.stream
+            .as_ref()
+            .expect("stream is present until commit")
+            .len()
+    }
+}
+
+impl Read for AtomicFile {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        self.stream().read(buffer)
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.stream().write(bytes)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.stream().flush()
+    }
+}
+
+impl ReadStream for AtomicFile {}
+impl WriteStream for AtomicFile {}
@@ -0,0 +1,329 @@
+//! Stream that computes a checksum over the bytes passing through it.
+use crate::{BinaryError, Endian, ReadStream, Result, SeekStream, WriteStream};
+use std::hash::Hasher;
+use std::io::{Read, Write};
+
+/// Trait for checksums computed over a stream of bytes.
+pub trait Checksum {
+    /// Number of bytes used to store the digest in a trailer.
+    const SIZE: usize;
+
+    /// Feed bytes into the checksum.
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Get the digest of all bytes fed since the last reset.
+    fn digest(&self) -> u64;
+
+    /// Reset the checksum to its initial state.
+    fn reset(&mut self);
+}
+
+const fn crc32_table(polynomial: u32) -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ polynomial
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+const CRC32_TABLE: [u32; 256] = crc32_table(0xEDB8_8320);
+const CRC32C_TABLE: [u32; 256] = crc32_table(0x82F6_3B78);
+
+fn crc32_update(table: &[u32; 256], mut crc: u32, bytes: &[u8]) -> u32 {
+    for byte in bytes {
+        crc = table[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    crc
+}
+
+/// CRC-32 checksum (IEEE 802.3) as used by zip, gzip and png.
+#[derive(Debug, Clone)]
+pub struct Crc32 {
+    crc: u32,
+}
+
+impl Crc32 {
+    /// Create a CRC-32 checksum.
+    pub fn new() -> Self {
+        Self { crc: !0 }
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Checksum for Crc32 {
+    const SIZE: usize = 4;
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.crc = crc32_update(&CRC32_TABLE, self.crc, bytes);
+    }
+
+    fn digest(&self) -> u64 {
+        !self.crc as u64
+    }
+
+    fn reset(&mut self) {
+        self.crc = !0;
+    }
+}
+
+/// CRC-32C checksum (Castagnoli) as used by iSCSI and ext4.
+#[derive(Debug, Clone)]
+pub struct Crc32c {
+    crc: u32,
+}
+
+impl Crc32c {
+    /// Create a CRC-32C checksum.
+    pub fn new() -> Self {
+        Self { crc: !0 }
+    }
+}
+
+impl Default for Crc32c {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Checksum for Crc32c {
+    const SIZE: usize = 4;
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.crc = crc32_update(&CRC32C_TABLE, self.crc, bytes);
+    }
+
+    fn digest(&self) -> u64 {
+        !self.crc as u64
+    }
+
+    fn reset(&mut self) {
+        self.crc = !0;
+    }
+}
+
+/// Adler-32 checksum as used by zlib.
+#[derive(Debug, Clone)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    /// Create an Adler-32 checksum.
+    pub fn new() -> Self {
+        Self { a: 1, b: 0 }
+    }
+}
+
+impl Default for Adler32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Checksum for Adler32 {
+    const SIZE: usize = 4;
+
+    fn update(&mut self, bytes: &[u8]) {
+        const MODULUS: u32 = 65521;
+        // Largest number of bytes that can be summed before `b` may overflow.
+        const CHUNK: usize = 5552;
+        for chunk in bytes.chunks(CHUNK) {
+            for byte in chunk {
+                self.a += *byte as u32;
+                self.b += self.a;
+            }
+            self.a %= MODULUS;
+            self.b %= MODULUS;
+        }
+    }
+
+    fn digest(&self) -> u64 {
+        ((self.b << 16) | self.a) as u64
+    }
+
+    fn reset(&mut self) {
+        self.a = 1;
+        self.b = 0;
+    }
+}
+
+/// Checksum backed by a `std::hash::Hasher`.
+///
+/// The hasher is recreated from `Default` when the checksum is reset.
+#[derive(Debug, Clone, Default)]
+pub struct HasherChecksum<H: Hasher + Default> {
+    hasher: H,
+}
+
+impl<H: Hasher + Default> HasherChecksum<H> {
+    /// Create a checksum from a default hasher.
+    pub fn new() -> Self {
+        Self {
+            hasher: H::default(),
+        }
+    }
+}
+
+impl<H: Hasher + Default> Checksum for HasherChecksum<H> {
+    const SIZE: usize = 8;
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.hasher.write(bytes);
+    }
+
+    fn digest(&self) -> u64 {
+        self.hasher.finish()
+    }
+
+    fn reset(&mut self) {
+        self.hasher = H::default();
+    }
+}
+
+/// Stream that feeds every byte read or written into a checksum.
+///
+/// The digest covers bytes in the order they pass through the stream,
+/// so seeking is only supported to the current position. Backpatching,
+/// such as filling a `Placeholder` or a fixed width length prefix,
+/// fails with `BinaryError::Unsupported` instead of corrupting the digest.
+pub struct ChecksumStream<S, C: Checksum> {
+    inner: S,
+    checksum: C,
+}
+
+impl<S, C: Checksum> ChecksumStream<S, C> {
+    /// Create a checksum stream wrapping another stream.
+    pub fn new(inner: S, checksum: C) -> Self {
+        Self { inner, checksum }
+    }
+
+    /// Get the digest of all bytes passed through since the last reset.
+    pub fn digest(&self) -> u64 {
+        self.checksum.digest()
+    }
+
+    /// Reset the checksum.
+    pub fn reset(&mut self) {
+        self.checksum.reset();
+    }
+
+    /// Get a reference to the checksum.
+    pub fn checksum(&self) -> &C {
+        &self.checksum
+    }
+
+    /// Get a reference to the wrapped stream.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped stream.
+    ///
+    /// Bytes read or written directly are not checksummed.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Consume the checksum stream and return the wrapped stream.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Write, C: Checksum> ChecksumStream<S, C> {
+    /// Write the digest as a trailer and reset the checksum.
+    ///
+    /// The trailer itself is not included in the checksum.
+    pub fn write_trailer(&mut self, endian: Endian) -> Result<usize> {
+        let digest = self.checksum.digest();
+        let bytes = if endian.is_little() {
+            digest.to_le_bytes()[..C::SIZE].to_vec()
+        } else {
+            digest.to_be_bytes()[8 - C::SIZE..].to_vec()
+        };
+        self.inner.write_all(&bytes)?;
+        self.checksum.reset();
+        Ok(bytes.len())
+    }
+}
+
+impl<S: Read, C: Checksum> ChecksumStream<S, C> {
+    /// Read a digest trailer, compare it to the computed digest
+    /// and reset the checksum.
+    ///
+    /// The trailer itself is not included in the checksum.
+    pub fn verify_trailer(&mut self, endian: Endian) -> Result<()> {
+        let actual = self.checksum.digest();
+        let mut bytes = [0u8; 8];
+        let expected = if endian.is_little() {
+            self.inner.read_exact(&mut bytes[..C::SIZE])?;
+            u64::from_le_bytes(bytes)
+        } else {
+            self.inner.read_exact(&mut bytes[8 - C::SIZE..])?;
+            u64::from_be_bytes(bytes)
+        };
+        self.checksum.reset();
+        if expected != actual {
+            return Err(BinaryError::ChecksumMismatch { expected, actual });
+        }
+        Ok(())
+    }
+}
+
+impl<S: SeekStream, C: Checksum> SeekStream for ChecksumStream<S, C> {
+    fn seek(&mut self, to: usize) -> Result<usize> {
+        if to != self.inner.tell()? {
+            return Err(BinaryError::Unsupported("seek in a checksum stream"));
+        }
+        Ok(to)
+    }
+
+    fn tell(&mut self) -> Result<usize> {
+        self.inner.tell()
+    }
+
+    fn len(&self) -> Result<usize> {
+        self.inner.len()
+    }
+}
+
+impl<S: Read, C: Checksum> Read for ChecksumStream<S, C> {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buffer)?;
+        self.checksum.update(&buffer[..read]);
+        Ok(read)
+    }
+}
+
+impl<S: Write, C: Checksum> Write for ChecksumStream<S, C> {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(bytes)?;
+        self.checksum.update(&bytes[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<S: ReadStream, C: Checksum> ReadStream for ChecksumStream<S, C> {}
+impl<S: WriteStream, C: Checksum> WriteStream for ChecksumStream<S, C> {}
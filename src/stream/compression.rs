@@ -0,0 +1,312 @@
+//! Streams that compress and decompress a section of another stream.
+use crate::{BinaryError, ReadStream, Result, SeekStream, WriteStream};
+use std::io::{BufReader, Read, Write};
+
+/// Compression formats supported by the compression streams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// Raw deflate data.
+    #[cfg(feature = "deflate")]
+    Deflate,
+    /// Deflate data with a zlib header and Adler-32 trailer.
+    #[cfg(feature = "deflate")]
+    Zlib,
+    /// Deflate data with a gzip header and CRC-32 trailer.
+    #[cfg(feature = "deflate")]
+    Gzip,
+    /// A single Zstandard frame.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+/// Counts the bytes passing through a stream.
+struct Counter<S> {
+    inner: S,
+    count: usize,
+}
+
+impl<S: ReadStream> Read for Counter<S> {
+    /// Reads are clamped to the end of the stream as the buffered
+    /// decoders read ahead in chunks, streams without a known length
+    /// are read unclamped.
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self
+            .inner
+            .len()
+            .and_then(|len| Ok(len.saturating_sub(self.inner.tell()?)));
+        let length = match remaining {
+            Ok(remaining) => buffer.len().min(remaining),
+            Err(_) => buffer.len(),
+        };
+        let read = self.inner.read(&mut buffer[..length])?;
+        self.count += read;
+        Ok(read)
+    }
+}
+
+impl<S: Write> Write for Counter<S> {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(bytes)?;
+        self.count += written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+enum Encoder<W: Write> {
+    #[cfg(feature = "deflate")]
+    Deflate(flate2::write::DeflateEncoder<Counter<W>>),
+    #[cfg(feature = "deflate")]
+    Zlib(flate2::write::ZlibEncoder<Counter<W>>),
+    #[cfg(feature = "deflate")]
+    Gzip(flate2::write::GzEncoder<Counter<W>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, Counter<W>>),
+}
+
+macro_rules! encoder {
+    ($encoder:expr, $inner:ident => $body:expr) => {
+        match $encoder {
+            #[cfg(feature = "deflate")]
+            Encoder::Deflate($inner) => $body,
+            #[cfg(feature = "deflate")]
+            Encoder::Zlib($inner) => $body,
+            #[cfg(feature = "deflate")]
+            Encoder::Gzip($inner) => $body,
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd($inner) => $body,
+        }
+    };
+}
+
+/// Stream that compresses everything written to it into another stream.
+///
+/// Call `finish()` once the section is written to flush the
+/// remaining compressed data and get the wrapped stream back.
+pub struct CompressWriter<W: Write> {
+    encoder: Encoder<W>,
+    position: usize,
+}
+
+impl<W: Write> CompressWriter<W> {
+    /// Create a compression stream writing to another stream.
+    pub fn new(inner: W, codec: Codec) -> Result<Self> {
+        let inner = Counter { inner, count: 0 };
+        let encoder = match codec {
+            #[cfg(feature = "deflate")]
+            Codec::Deflate => Encoder::Deflate(flate2::write::DeflateEncoder::new(
+                inner,
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "deflate")]
+            Codec::Zlib => Encoder::Zlib(flate2::write::ZlibEncoder::new(
+                inner,
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "deflate")]
+            Codec::Gzip => Encoder::Gzip(flate2::write::GzEncoder::new(
+                inner,
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => Encoder::Zstd(zstd::stream::write::Encoder::new(inner, 0)?),
+        };
+        Ok(Self {
+            encoder,
+            position: 0,
+        })
+    }
+
+    /// Get the number of uncompressed bytes written.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Get the number of compressed bytes written to the wrapped stream.
+    ///
+    /// Encoders buffer data internally so this lags behind until
+    /// the stream is flushed or finished.
+    pub fn compressed_position(&self) -> usize {
+        encoder!(&self.encoder, encoder => encoder.get_ref().count)
+    }
+
+    /// Finish the compressed section and return the wrapped stream.
+    pub fn finish(self) -> Result<W> {
+        let inner = encoder!(self.encoder, encoder => encoder.finish()?);
+        Ok(inner.inner)
+    }
+}
+
+impl<W: Write> SeekStream for CompressWriter<W> {
+    fn seek(&mut self, to: usize) -> Result<usize> {
+        if to != self.position {
+            return Err(BinaryError::Unsupported("seek in compression stream"));
+        }
+        Ok(self.position)
+    }
+
+    fn tell(&mut self) -> Result<usize> {
+        Ok(self.position)
+    }
+
+    fn len(&self) -> Result<usize> {
+        Ok(self.position)
+    }
+}
+
+impl<W: Write> Write for CompressWriter<W> {
+    /// Writes are passed to the encoder in full, the encoders accept
+    /// only part of a large buffer per call and callers such as
+    /// `BinaryWriter::write_bytes()` write once.
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        encoder!(&mut self.encoder, encoder => encoder.write_all(bytes)?);
+        self.position += bytes.len();
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        encoder!(&mut self.encoder, encoder => encoder.flush())
+    }
+}
+
+impl<W: Write> WriteStream for CompressWriter<W> {}
+
+enum Decoder<R: ReadStream> {
+    #[cfg(feature = "deflate")]
+    Deflate(flate2::bufread::DeflateDecoder<BufReader<Counter<R>>>),
+    #[cfg(feature = "deflate")]
+    Zlib(flate2::bufread::ZlibDecoder<BufReader<Counter<R>>>),
+    #[cfg(feature = "deflate")]
+    Gzip(flate2::bufread::GzDecoder<BufReader<Counter<R>>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::read::Decoder<'static, BufReader<Counter<R>>>),
+}
+
+macro_rules! decoder {
+    ($decoder:expr, $inner:ident => $body:expr) => {
+        match $decoder {
+            #[cfg(feature = "deflate")]
+            Decoder::Deflate($inner) => $body,
+            #[cfg(feature = "deflate")]
+            Decoder::Zlib($inner) => $body,
+            #[cfg(feature = "deflate")]
+            Decoder::Gzip($inner) => $body,
+            #[cfg(feature = "zstd")]
+            Decoder::Zstd($inner) => $body,
+        }
+    };
+}
+
+/// Stream that decompresses a section of another stream.
+///
+/// The wrapped stream is read ahead in chunks, call `finish()` to
+/// rewind it to the first byte after the compressed section.
+pub struct CompressReader<R: ReadStream> {
+    decoder: Decoder<R>,
+    position: usize,
+}
+
+impl<R: ReadStream> CompressReader<R> {
+    /// Create a decompression stream reading from another stream.
+    pub fn new(inner: R, codec: Codec) -> Result<Self> {
+        let inner = BufReader::new(Counter { inner, count: 0 });
+        let decoder = match codec {
+            #[cfg(feature = "deflate")]
+            Codec::Deflate => Decoder::Deflate(flate2::bufread::DeflateDecoder::new(inner)),
+            #[cfg(feature = "deflate")]
+            Codec::Zlib => Decoder::Zlib(flate2::bufread::ZlibDecoder::new(inner)),
+            #[cfg(feature = "deflate")]
+            Codec::Gzip => Decoder::Gzip(flate2::bufread::GzDecoder::new(inner)),
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => {
+                Decoder::Zstd(zstd::stream::read::Decoder::with_buffer(inner)?.single_frame())
+            }
+        };
+        Ok(Self {
+            decoder,
+            position: 0,
+        })
+    }
+
+    /// Get the number of uncompressed bytes read.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Get the number of compressed bytes consumed from the wrapped stream.
+    pub fn compressed_position(&self) -> usize {
+        let buffered = decoder!(&self.decoder, decoder => decoder.get_ref());
+        buffered.get_ref().count - buffered.buffer().len()
+    }
+
+    /// Finish the compressed section and return the wrapped stream.
+    ///
+    /// Any uncompressed data that was not read is skipped and the
+    /// wrapped stream is positioned after the compressed section.
+    ///
+    /// The decoder reads ahead of the compressed section, so the
+    /// wrapped stream must be able to seek back to its end; streams
+    /// that cannot seek fail with `BinaryError::Unsupported`.
+    pub fn finish(mut self) -> Result<R> {
+        std::io::copy(&mut self, &mut std::io::sink())?;
+        let buffered = match self.decoder {
+            #[cfg(feature = "deflate")]
+            Decoder::Deflate(decoder) => decoder.into_inner(),
+            #[cfg(feature = "deflate")]
+            Decoder::Zlib(decoder) => decoder.into_inner(),
+            #[cfg(feature = "deflate")]
+            Decoder::Gzip(decoder) => decoder.into_inner(),
+            #[cfg(feature = "zstd")]
+            Decoder::Zstd(decoder) => decoder.finish(),
+        };
+        let unread = buffered.buffer().len();
+        let mut inner = buffered.into_inner().inner;
+        let position = inner.tell()?;
+        inner.seek(position - unread)?;
+        Ok(inner)
+    }
+}
+
+impl<R: ReadStream> SeekStream for CompressReader<R> {
+    /// Seeking is only supported forwards, the skipped
+    /// data is decompressed and discarded.
+    fn seek(&mut self, to: usize) -> Result<usize> {
+        if to < self.position {
+            return Err(BinaryError::Unsupported(
+                "seek backwards in compression stream",
+            ));
+        }
+        let offset = self.position;
+        let skip = (to - offset) as u64;
+        let skipped = std::io::copy(&mut self.by_ref().take(skip), &mut std::io::sink())?;
+        if skipped < skip {
+            return Err(BinaryError::ReadPastEof {
+                offset,
+                requested: skip as usize,
+                available: skipped as usize,
+            });
+        }
+        Ok(self.position)
+    }
+
+    fn tell(&mut self) -> Result<usize> {
+        Ok(self.position)
+    }
+
+    fn len(&self) -> Result<usize> {
+        Err(BinaryError::Unsupported("length of compression stream"))
+    }
+}
+
+impl<R: ReadStream> Read for CompressReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let read = decoder!(&mut self.decoder, decoder => decoder.read(buffer)?);
+        self.position += read;
+        Ok(read)
+    }
+}
+
+impl<R: ReadStream> ReadStream for CompressReader<R> {}
@@ -1,24 +1,56 @@
 //! Stream for operating on files.
-use crate::{BinaryError, ReadStream, Result, SeekStream, WriteStream};
+use crate::stream::{read_end, write_end};
+use crate::{ReadStream, Result, SeekPastEnd, SeekStream, WriteStream};
 use std::fs::{File, Metadata, OpenOptions};
 use std::io::prelude::*;
-use std::io::{Error, ErrorKind, Read, SeekFrom, Write};
+use std::io::{Read, SeekFrom, Write};
 use std::path::Path;
 
 /// Stream that wraps a file.
+///
+/// The length of the stream is tracked as writes extend the file so
+/// a single handle can interleave writes and reads.
+///
+/// Streams are unbuffered unless created with `buffered()`. Buffered
+/// writes are written to the file when the stream is flushed, when a
+/// read or a write elsewhere in the file needs it, or when the stream
+/// is dropped; errors are ignored on drop so call `flush()` or
+/// `sync_all()` to observe them.
 pub struct FileStream {
     file: File,
-    metadata: std::result::Result<Metadata, std::io::Error>,
+    /// Position of the file cursor, `None` after a failed operation.
+    cursor: Option<usize>,
+    position: usize,
+    length: usize,
+    capacity: usize,
+    /// Bytes read ahead starting at `read_start`.
+    read_buffer: Vec<u8>,
+    read_start: usize,
+    /// Bytes waiting to be written starting at `write_start`.
+    write_buffer: Vec<u8>,
+    write_start: usize,
+    seek_past_end: SeekPastEnd,
 }
 
 impl FileStream {
     /// Create a file stream.
-    pub fn new(file: File) -> Self {
-
-        Self {
-            metadata: file.metadata(),
+    ///
+    /// The stream starts at the current position of the file.
+    pub fn new(mut file: File) -> Result<Self> {
+        let length = file.metadata()?.len().try_into()?;
+        let position = file.stream_position()?.try_into()?;
+        Ok(Self {
             file,
-        }
+            cursor: Some(position),
+            position,
+            length,
+            capacity: 0,
+            read_buffer: Vec::new(),
+            read_start: 0,
+            write_buffer: Vec::new(),
+            write_start: 0,
+            seek_past_end: Default::default(),
+        })
     }
 
     /// Create a file stream in write-only mode.
@@ -26,66 +58,210 @@ impl FileStream {
     /// If the file exists it is truncated, if it does not
     /// exist it will be created.
     pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
-        Ok(FileStream::new(File::create(path.as_ref())?))
+        FileStream::new(File::create(path.as_ref())?)
     }
 
     /// Attempts to open a file stream in read-only mode.
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        Ok(FileStream::new(File::open(path.as_ref())?))
+        FileStream::new(File::open(path.as_ref())?)
     }
 
     /// Attempts to open a file stream with read and write modes enabled.
     pub fn write<P: AsRef<Path>>(path: P) -> Result<Self> {
-        Ok(FileStream::new(
-            OpenOptions::new().read(true).write(true).open(path)?,
-        ))
+        FileStream::new(OpenOptions::new().read(true).write(true).open(path)?)
+    }
+
+    /// Buffer reads and writes using buffers of the given capacity.
+    ///
+    /// Reads and writes of at least `capacity` bytes bypass the buffers.
+    pub fn buffered(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self.read_buffer = Vec::with_capacity(capacity);
+        self.write_buffer = Vec::with_capacity(capacity);
+        self
+    }
+
+    /// Attempts to get the metadata for file.
+    ///
+    /// Buffered writes are not reflected until the stream is flushed.
+    pub fn metadata(&self) -> std::io::Result<Metadata> {
+        self.file.metadata()
+    }
+
+    seek_past_end_accessors!();
+
+    /// Write buffered data and wait for all data and metadata
+    /// to reach the disk.
+    pub fn sync_all(&mut self) -> Result<()> {
+        self.flush()?;
+        Ok(self.file.sync_all()?)
+    }
+
+    /// Write buffered data and wait for the data to reach the disk.
+    ///
+    /// Metadata such as modification times may not be synchronized.
+    pub fn sync_data(&mut self) -> Result<()> {
+        self.flush()?;
+        Ok(self.file.sync_data()?)
+    }
+
+    /// Truncate or extend the file to `length` bytes.
+    ///
+    /// Extending the file fills it with zeros, the position is unchanged.
+    pub fn set_len(&mut self, length: usize) -> Result<()> {
+        self.flush_write_buffer()?;
+        self.read_buffer.clear();
+        self.file.set_len(length as u64)?;
+        self.length = length;
+        Ok(())
+    }
+
+    /// Move the file cursor to a position if it is not already there.
+    fn seek_file(&mut self, to: usize) -> std::io::Result<()> {
+        if self.cursor != Some(to) {
+            self.cursor = None;
+            self.file.seek(SeekFrom::Start(to as u64))?;
+            self.cursor = Some(to);
+        }
+        Ok(())
+    }
+
+    /// Write `bytes` to the file at a position.
+    fn write_at(&mut self, to: usize, bytes: &[u8], all: bool) -> std::io::Result<usize> {
+        self.seek_file(to)?;
+        self.cursor = None;
+        let written = if all {
+            self.file.write_all(bytes)?;
+            bytes.len()
+        } else {
+            self.file.write(bytes)?
+        };
+        self.cursor = Some(to + written);
+        Ok(written)
+    }
+
+    /// Fill `buffer` from the file at a position.
+    fn read_at(&mut self, from: usize, buffer: &mut [u8]) -> std::io::Result<()> {
+        self.seek_file(from)?;
+        self.cursor = None;
+        self.file.read_exact(buffer)?;
+        self.cursor = Some(from + buffer.len());
+        Ok(())
     }
 
-    /// Attempts to get the metadata for file
-    pub fn metadata(&self) -> std::io::Result<&Metadata> {
-        match self.metadata.as_ref() {
-            Ok(v) => Ok(v),
-            Err(e) => Err(std::io::Error::new(e.kind(), "Unable to get metadata"))
+    /// Write the buffered data to the file.
+    fn flush_write_buffer(&mut self) -> std::io::Result<()> {
+        if !self.write_buffer.is_empty() {
+            let buffer = std::mem::take(&mut self.write_buffer);
+            let result = self.write_at(self.write_start, &buffer, true);
+            self.write_buffer = buffer;
+            result?;
+            self.write_buffer.clear();
         }
+        Ok(())
+    }
+
+    fn advance(&mut self, amount: usize) {
+        self.position += amount;
+        self.length = self.length.max(self.position);
     }
 }
 
 impl SeekStream for FileStream {
     fn seek(&mut self, to: usize) -> Result<usize> {
-        Ok(self.file.seek(SeekFrom::Start(to as u64))? as usize)
+        self.seek_past_end.check(to, self.length)?;
+        self.position = to;
+        Ok(self.position)
     }
 
     fn tell(&mut self) -> Result<usize> {
-        Ok(self.file.seek(SeekFrom::Current(0))? as usize)
+        Ok(self.position)
     }
 
     fn len(&self) -> Result<usize> {
-        Ok(self.metadata()?.len().try_into()?)
+        Ok(self.length)
     }
 }
 
 impl Read for FileStream {
     fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
-        if self.tell().unwrap() + buffer.len() > self.metadata()?.len() as usize {
-            return Err(Error::new(
-                ErrorKind::UnexpectedEof,
-                BinaryError::ReadPastEof,
-            ));
+        let end = read_end(self.position, buffer.len(), self.length)?;
+
+        self.flush_write_buffer()?;
+
+        let buffered =
+            self.read_start <= self.position && end <= self.read_start + self.read_buffer.len();
+        if !buffered {
+            if buffer.len() >= self.capacity {
+                self.read_at(self.position, buffer)?;
+                self.position = end;
+                return Ok(buffer.len());
+            }
+
+            let fill = self.capacity.min(self.length - self.position);
+            let mut read_buffer = std::mem::take(&mut self.read_buffer);
+            read_buffer.resize(fill, 0);
+            let result = self.read_at(self.position, &mut read_buffer);
+            if result.is_err() {
+                read_buffer.clear();
+            }
+            self.read_buffer = read_buffer;
+            self.read_start = self.position;
+            result?;
         }
-        
-        self.file.read(buffer)
+
+        let offset = self.position - self.read_start;
+        buffer.copy_from_slice(&self.read_buffer[offset..offset + buffer.len()]);
+        self.position = end;
+
+        Ok(buffer.len())
     }
 }
 
 impl Write for FileStream {
     fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
-        self.file.write(bytes)
+        // An empty write past the end leaves the length unchanged
+        if bytes.is_empty() {
+            return Ok(0);
+        }
+        write_end(self.position, bytes.len(), usize::MAX)?;
+        self.read_buffer.clear();
+
+        let contiguous = self.write_start + self.write_buffer.len() == self.position;
+        if !contiguous || self.write_buffer.len() + bytes.len() > self.capacity {
+            self.flush_write_buffer()?;
+        }
+
+        if bytes.len() >= self.capacity {
+            let written = self.write_at(self.position, bytes, false)?;
+            self.advance(written);
+            return Ok(written);
+        }
+
+        if self.write_buffer.is_empty() {
+            self.write_start = self.position;
+        }
+        self.write_buffer.extend_from_slice(bytes);
+        self.advance(bytes.len());
+
+        Ok(bytes.len())
     }
 
+    /// Write buffered data to the file.
+    ///
+    /// Use `sync_all()` or `sync_data()` to also wait for the
+    /// data to reach the disk.
     fn flush(&mut self) -> std::io::Result<()> {
+        self.flush_write_buffer()?;
         self.file.flush()
     }
 }
 
+impl Drop for FileStream {
+    fn drop(&mut self) {
+        let _ = self.flush_write_buffer();
+    }
+}
+
 impl ReadStream for FileStream {}
 impl WriteStream for FileStream {}
@@ -0,0 +1,80 @@
+//! Stream over a reader or writer that cannot seek.
+use crate::{BinaryError, ReadStream, Result, SeekStream, WriteStream};
+use std::io::{Read, Write};
+
+/// Stream that wraps a `Read` or `Write` implementation that cannot
+/// seek, such as a socket or a pipe.
+///
+/// The position counts the bytes read or written. Seeking is only
+/// supported to the current position and the length is unknown.
+/// Unlike the other streams reads may return fewer bytes than
+/// requested, `BinaryReader` waits for the remaining bytes. Writes
+/// are retried until the whole buffer is written.
+pub struct IoStream<T> {
+    inner: T,
+    position: usize,
+}
+
+impl<T> IoStream<T> {
+    /// Create a stream wrapping a reader or writer.
+    pub fn new(inner: T) -> Self {
+        Self { inner, position: 0 }
+    }
+
+    /// Get a reference to the wrapped reader or writer.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped reader or writer.
+    ///
+    /// Bytes read or written directly are not counted.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consume the stream and return the wrapped reader or writer.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> SeekStream for IoStream<T> {
+    fn seek(&mut self, to: usize) -> Result<usize> {
+        if to != self.position {
+            return Err(BinaryError::Unsupported("seek in an unseekable stream"));
+        }
+        Ok(self.position)
+    }
+
+    fn tell(&mut self) -> Result<usize> {
+        Ok(self.position)
+    }
+
+    fn len(&self) -> Result<usize> {
+        Err(BinaryError::Unsupported("length of an unseekable stream"))
+    }
+}
+
+impl<T: Read> Read for IoStream<T> {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buffer)?;
+        self.position += read;
+        Ok(read)
+    }
+}
+
+impl<T: Write> Write for IoStream<T> {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.inner.write_all(bytes)?;
+        self.position += bytes.len();
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: Read> ReadStream for IoStream<T> {}
+impl<T: Write> WriteStream for IoStream<T> {}
@@ -1,11 +1,13 @@
 //! Stream that reads from and writes to an owned buffer.
-use crate::{BinaryError, ReadStream, Result, SeekStream, WriteStream};
+use crate::stream::{read_end, write_end};
+use crate::{ReadStream, Result, SeekPastEnd, SeekStream, WriteStream};
 use std::io::{Error, ErrorKind, Read, Write};
 
 /// Stream that wraps an owned buffer.
 pub struct MemoryStream {
     buffer: Vec<u8>,
     position: usize,
+    seek_past_end: SeekPastEnd,
 }
 
 impl MemoryStream {
@@ -14,12 +16,79 @@ impl MemoryStream {
         Self {
             buffer: Vec::new(),
             position: 0,
+            seek_past_end: Default::default(),
         }
     }
+
+    /// Create a memory stream with space for at least `capacity` bytes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buffer: Vec::with_capacity(capacity),
+            position: 0,
+            seek_past_end: Default::default(),
+        }
+    }
+
+    /// Get the contents of the stream.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Get the contents of the stream mutably.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.buffer
+    }
+
+    /// Get the number of bytes the stream can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    /// Remove all bytes and rewind to the start, keeping the capacity.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.position = 0;
+    }
+
+    /// Shorten the stream to `length` bytes.
+    ///
+    /// Has no effect if the stream is already shorter, the
+    /// position is moved back if it is past the new end.
+    pub fn truncate(&mut self, length: usize) {
+        self.buffer.truncate(length);
+        self.position = self.position.min(self.buffer.len());
+    }
+
+    /// Resize the stream to `length` bytes, new bytes are zeroed.
+    ///
+    /// The position is moved back if it is past the new end.
+    pub fn set_len(&mut self, length: usize) {
+        self.buffer.resize(length, 0);
+        self.position = self.position.min(length);
+    }
+
+    /// Reserve space for at least `additional` more bytes.
+    pub fn reserve(&mut self, additional: usize) {
+        self.buffer.reserve(additional);
+    }
+
+    /// Shrink the capacity of the stream to fit its length.
+    pub fn shrink_to_fit(&mut self) {
+        self.buffer.shrink_to_fit();
+    }
+
+    seek_past_end_accessors!();
+}
+
+impl Default for MemoryStream {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SeekStream for MemoryStream {
     fn seek(&mut self, to: usize) -> Result<usize> {
+        self.seek_past_end.check(to, self.buffer.len())?;
         self.position = to;
         Ok(self.position)
     }
@@ -35,20 +104,9 @@ impl SeekStream for MemoryStream {
 
 impl Read for MemoryStream {
     fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
-        if self.position + buffer.len() > self.buffer.len() {
-            return Err(Error::new(
-                ErrorKind::UnexpectedEof,
-                BinaryError::ReadPastEof,
-            ));
-        }
-
-        let mut idx = 0;
-        for i in self.position..self.position + buffer.len() {
-            buffer[idx] = self.buffer[i];
-            idx += 1;
-        }
-
-        self.position += buffer.len();
+        let end = read_end(self.position, buffer.len(), self.buffer.len())?;
+        buffer.copy_from_slice(&self.buffer[self.position..end]);
+        self.position = end;
 
         Ok(buffer.len())
     }
@@ -56,22 +114,25 @@ impl Read for MemoryStream {
 
 impl Write for MemoryStream {
     fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
-        let bytes_to_end = self.buffer.len() - self.position;
-        if bytes.len() > bytes_to_end {
-            let bytes_out_of_buffer = bytes.len() - bytes_to_end;
-
-            for _ in 0..bytes_out_of_buffer {
-                self.buffer.push(0);
-            }
+        // An empty write past the end leaves the length unchanged
+        if bytes.is_empty() {
+            return Ok(0);
         }
-
-        let mut idx = 0;
-        for i in self.position..self.position + bytes.len() {
-            self.buffer[i] = bytes[idx];
-            idx += 1;
+        let end = write_end(self.position, bytes.len(), usize::MAX)?;
+        if end > self.buffer.len() {
+            self.buffer
+                .try_reserve(end - self.buffer.len())
+                .map_err(|error| Error::new(ErrorKind::OutOfMemory, error))?;
         }
+        if self.position > self.buffer.len() {
+            self.buffer.resize(self.position, 0);
+        }
+
+        let overlap = bytes.len().min(self.buffer.len() - self.position);
+        self.buffer[self.position..self.position + overlap].copy_from_slice(&bytes[..overlap]);
+        self.buffer.extend_from_slice(&bytes[overlap..]);
 
-        self.position += bytes.len();
+        self.position = end;
 
         Ok(bytes.len())
     }
@@ -86,10 +147,12 @@ impl From<Vec<u8>> for MemoryStream {
         MemoryStream {
             buffer,
             position: 0,
+            seek_past_end: Default::default(),
         }
     }
 }
 
+#[allow(clippy::from_over_into)]
 impl Into<Vec<u8>> for MemoryStream {
     fn into(self) -> Vec<u8> {
         self.buffer
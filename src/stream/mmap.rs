@@ -0,0 +1,205 @@
+//! Streams backed by memory-mapped files.
+use crate::stream::{read_end, write_end};
+use crate::{ReadStream, Result, SeekPastEnd, SeekStream, WriteStream};
+use memmap2::{Mmap, MmapMut};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Read-only stream over a memory-mapped file.
+///
+/// Reads are copies out of the mapping so no system calls are made
+/// per read, use `as_slice()` or `read_slice()` to borrow the mapped
+/// bytes without copying.
+pub struct MmapStream {
+    map: Mmap,
+    position: usize,
+    seek_past_end: SeekPastEnd,
+}
+
+impl MmapStream {
+    /// Map a file into memory.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any
+    /// other process, while it is mapped.
+    pub unsafe fn new(file: &File) -> Result<Self> {
+        Ok(Self {
+            map: Mmap::map(file)?,
+            position: 0,
+            seek_past_end: Default::default(),
+        })
+    }
+
+    /// Open a file and map it into memory.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any
+    /// other process, while it is mapped.
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(&File::open(path.as_ref())?)
+    }
+
+    /// Get the mapped bytes.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// Borrow the next `length` bytes and advance the position.
+    pub fn read_slice(&mut self, length: usize) -> Result<&[u8]> {
+        let end = read_end(self.position, length, self.map.len())?;
+        let bytes = &self.map[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    seek_past_end_accessors!();
+}
+
+impl SeekStream for MmapStream {
+    fn seek(&mut self, to: usize) -> Result<usize> {
+        self.seek_past_end.check(to, self.map.len())?;
+        self.position = to;
+        Ok(self.position)
+    }
+
+    fn tell(&mut self) -> Result<usize> {
+        Ok(self.position)
+    }
+
+    fn len(&self) -> Result<usize> {
+        Ok(self.map.len())
+    }
+}
+
+impl Read for MmapStream {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let end = read_end(self.position, buffer.len(), self.map.len())?;
+        buffer.copy_from_slice(&self.map[self.position..end]);
+        self.position = end;
+
+        Ok(buffer.len())
+    }
+}
+
+impl ReadStream for MmapStream {}
+
+/// Writable stream over a memory-mapped file.
+///
+/// The length of the stream is fixed to the length of the file
+/// when it is mapped, writing past the end is an error. Changes are
+/// written back by the operating system at an unspecified time,
+/// call `flush()` to write them to disk before continuing.
+pub struct MmapStreamMut {
+    map: MmapMut,
+    position: usize,
+    seek_past_end: SeekPastEnd,
+}
+
+impl MmapStreamMut {
+    /// Map a file opened for reading and writing into memory.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any
+    /// other process, while it is mapped.
+    pub unsafe fn new(file: &File) -> Result<Self> {
+        Ok(Self {
+            map: MmapMut::map_mut(file)?,
+            position: 0,
+            seek_past_end: Default::default(),
+        })
+    }
+
+    /// Open an existing file for reading and writing and map it into memory.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any
+    /// other process, while it is mapped.
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(&OpenOptions::new().read(true).write(true).open(path)?)
+    }
+
+    /// Create a zero filled file of the given length and map it into memory.
+    ///
+    /// If the file exists it is truncated.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any
+    /// other process, while it is mapped.
+    pub unsafe fn create<P: AsRef<Path>>(path: P, length: usize) -> Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(length as u64)?;
+        Self::new(&file)
+    }
+
+    /// Get the mapped bytes.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// Get the mapped bytes mutably.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.map
+    }
+
+    /// Write changes back to disk without waiting for them to complete.
+    pub fn flush_async(&self) -> Result<()> {
+        Ok(self.map.flush_async()?)
+    }
+
+    seek_past_end_accessors!();
+}
+
+impl SeekStream for MmapStreamMut {
+    fn seek(&mut self, to: usize) -> Result<usize> {
+        self.seek_past_end.check(to, self.map.len())?;
+        self.position = to;
+        Ok(self.position)
+    }
+
+    fn tell(&mut self) -> Result<usize> {
+        Ok(self.position)
+    }
+
+    fn len(&self) -> Result<usize> {
+        Ok(self.map.len())
+    }
+}
+
+impl Read for MmapStreamMut {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let end = read_end(self.position, buffer.len(), self.map.len())?;
+        buffer.copy_from_slice(&self.map[self.position..end]);
+        self.position = end;
+
+        Ok(buffer.len())
+    }
+}
+
+impl Write for MmapStreamMut {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        let end = write_end(self.position, bytes.len(), self.map.len())?;
+        self.map[self.position..end].copy_from_slice(bytes);
+        self.position = end;
+
+        Ok(bytes.len())
+    }
+
+    /// Write changes back to disk and wait for them to complete.
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.map.flush()
+    }
+}
+
+impl ReadStream for MmapStreamMut {}
+impl WriteStream for MmapStreamMut {}
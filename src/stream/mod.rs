@@ -1,3 +1,86 @@
+/// Implement the accessors for the `seek_past_end` field of a stream.
+macro_rules! seek_past_end_accessors {
+    () => {
+        /// Set the behaviour when seeking past the end of the stream.
+        pub fn set_seek_past_end(&mut self, seek_past_end: $crate::SeekPastEnd) {
+            self.seek_past_end = seek_past_end;
+        }
+
+        /// Get the behaviour when seeking past the end of the stream.
+        pub fn seek_past_end(&self) -> $crate::SeekPastEnd {
+            self.seek_past_end
+        }
+    };
+}
+
+pub(crate) mod atomic;
+pub(crate) mod checksum;
+#[cfg(any(feature = "deflate", feature = "zstd"))]
+pub(crate) mod compression;
 pub(crate) mod file;
+pub(crate) mod io;
 pub(crate) mod memory;
+#[cfg(feature = "mmap")]
+pub(crate) mod mmap;
 pub(crate) mod slice;
+
+use crate::{BinaryError, Result};
+
+/// Behaviour of a stream when seeking past its end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeekPastEnd {
+    /// Seeking past the end fails with `BinaryError::SeekOutOfBounds`
+    /// and the position is unchanged.
+    Error,
+    /// Seeking past the end succeeds, reads fail until data is written
+    /// and the next write fills the gap with zeros.
+    ///
+    /// Streams with a fixed size cannot grow so writing fails.
+    #[default]
+    ZeroFill,
+}
+
+impl SeekPastEnd {
+    /// Check a seek to `to` in a stream of `length` bytes.
+    pub(crate) fn check(&self, to: usize, length: usize) -> Result<()> {
+        if *self == SeekPastEnd::Error && to > length {
+            return Err(BinaryError::SeekOutOfBounds {
+                position: to,
+                length,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Create the error returned when a read would pass the end of a stream.
+fn read_past_eof(offset: usize, requested: usize, length: usize) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::UnexpectedEof,
+        BinaryError::ReadPastEof {
+            offset,
+            requested,
+            available: length.saturating_sub(offset),
+        },
+    )
+}
+
+/// Get the end of a read of `requested` bytes at `offset` in a stream
+/// of `length` bytes, failing if it would pass the end of the stream.
+pub(crate) fn read_end(offset: usize, requested: usize, length: usize) -> std::io::Result<usize> {
+    offset
+        .checked_add(requested)
+        .filter(|end| *end <= length)
+        .ok_or_else(|| read_past_eof(offset, requested, length))
+}
+
+/// Get the end of a write of `requested` bytes at `offset` in a stream
+/// that can hold up to `length` bytes, failing if it would pass the end.
+pub(crate) fn write_end(offset: usize, requested: usize, length: usize) -> std::io::Result<usize> {
+    offset
+        .checked_add(requested)
+        .filter(|end| *end <= length)
+        .ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::WriteZero, BinaryError::WritePastEnd)
+        })
+}
@@ -1,11 +1,13 @@
-//! Stream that reads from a slice of bytes.
-use crate::{BinaryError, ReadStream, Result, SeekStream};
-use std::io::{Error, ErrorKind, Read};
+//! Streams that read from and write to a slice of bytes.
+use crate::stream::{read_end, write_end};
+use crate::{ReadStream, Result, SeekPastEnd, SeekStream, WriteStream};
+use std::io::{Read, Write};
 
 /// Stream that wraps a slice of bytes.
 pub struct SliceStream<'a> {
     buffer: &'a [u8],
     position: usize,
+    seek_past_end: SeekPastEnd,
 }
 
 impl<'a> SliceStream<'a> {
@@ -14,12 +16,21 @@ impl<'a> SliceStream<'a> {
         Self {
             buffer,
             position: 0,
+            seek_past_end: Default::default(),
         }
     }
+
+    /// Get the wrapped slice.
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.buffer
+    }
+
+    seek_past_end_accessors!();
 }
 
 impl SeekStream for SliceStream<'_> {
     fn seek(&mut self, to: usize) -> Result<usize> {
+        self.seek_past_end.check(to, self.buffer.len())?;
         self.position = to;
         Ok(self.position)
     }
@@ -35,23 +46,94 @@ impl SeekStream for SliceStream<'_> {
 
 impl Read for SliceStream<'_> {
     fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
-        if self.position + buffer.len() > self.buffer.len() {
-            return Err(Error::new(
-                ErrorKind::UnexpectedEof,
-                BinaryError::ReadPastEof,
-            ));
-        }
+        let end = read_end(self.position, buffer.len(), self.buffer.len())?;
+        buffer.copy_from_slice(&self.buffer[self.position..end]);
+        self.position = end;
+
+        Ok(buffer.len())
+    }
+}
+
+impl ReadStream for SliceStream<'_> {}
+
+/// Stream that wraps a mutable slice of bytes.
+///
+/// Writes are made in place and the stream never grows, writing
+/// past the end of the slice fails with `BinaryError::WritePastEnd`
+/// and nothing is written.
+pub struct SliceStreamMut<'a> {
+    buffer: &'a mut [u8],
+    position: usize,
+    seek_past_end: SeekPastEnd,
+}
 
-        let mut idx = 0;
-        for i in self.position..self.position + buffer.len() {
-            buffer[idx] = self.buffer[i];
-            idx += 1;
+impl<'a> SliceStreamMut<'a> {
+    /// Create a mutable slice stream.
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        Self {
+            buffer,
+            position: 0,
+            seek_past_end: Default::default(),
         }
+    }
+
+    /// Get the wrapped slice.
+    pub fn as_slice(&self) -> &[u8] {
+        self.buffer
+    }
+
+    /// Get the wrapped slice mutably.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        self.buffer
+    }
 
-        self.position += buffer.len();
+    /// Get the number of bytes between the position and the end of the slice.
+    pub fn remaining(&self) -> usize {
+        self.buffer.len().saturating_sub(self.position)
+    }
+
+    seek_past_end_accessors!();
+}
+
+impl SeekStream for SliceStreamMut<'_> {
+    fn seek(&mut self, to: usize) -> Result<usize> {
+        self.seek_past_end.check(to, self.buffer.len())?;
+        self.position = to;
+        Ok(self.position)
+    }
+
+    fn tell(&mut self) -> Result<usize> {
+        Ok(self.position)
+    }
+
+    fn len(&self) -> Result<usize> {
+        Ok(self.buffer.len())
+    }
+}
+
+impl Read for SliceStreamMut<'_> {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let end = read_end(self.position, buffer.len(), self.buffer.len())?;
+        buffer.copy_from_slice(&self.buffer[self.position..end]);
+        self.position = end;
 
         Ok(buffer.len())
     }
 }
 
-impl ReadStream for SliceStream<'_> {}
+impl Write for SliceStreamMut<'_> {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        let end = write_end(self.position, bytes.len(), self.buffer.len())?;
+        self.buffer[self.position..end].copy_from_slice(bytes);
+        self.position = end;
+
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl ReadStream for SliceStreamMut<'_> {}
+impl WriteStream for SliceStreamMut<'_> {}
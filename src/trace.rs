@@ -0,0 +1,170 @@
+//! Trace of the values read from a stream.
+use crate::{BinaryReader, Result};
+use std::fmt::{self, Debug, Write};
+
+/// Number of bytes shown on each line of a hex dump.
+const BYTES_PER_LINE: usize = 16;
+
+/// Value read while tracing was enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// Name of the reader method, for example `read_u32`.
+    pub method: &'static str,
+    /// Stream offset the read started at.
+    pub offset: usize,
+    /// Raw bytes consumed by the read.
+    pub bytes: Vec<u8>,
+    /// Decoded value formatted with `Debug`.
+    pub value: String,
+    /// Path to the field being decoded, see `BinaryReader::path()`.
+    pub scope: String,
+}
+
+/// Log of the values read by a binary reader.
+///
+/// Created with `BinaryReader::start_trace()`, the trace can be
+/// rendered as an annotated hex dump using `Display`. Entries are
+/// grouped under the scope labels given to `field()` and `element()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trace {
+    entries: Vec<TraceEntry>,
+}
+
+impl Trace {
+    /// Get the entries in the order they were read.
+    pub fn entries(&self) -> &[TraceEntry] {
+        &self.entries
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut scope = "";
+        for entry in &self.entries {
+            if entry.scope != scope {
+                scope = &entry.scope;
+                if !scope.is_empty() {
+                    writeln!(f, "{}:", scope)?;
+                }
+            }
+            let indent = if scope.is_empty() { "" } else { "  " };
+            let mut lines = entry.bytes.chunks(BYTES_PER_LINE);
+            let first = lines.next().unwrap_or(&[]);
+            writeln!(
+                f,
+                "{}{:08x}  {}  {} {}",
+                indent,
+                entry.offset,
+                hex(first),
+                entry.method,
+                entry.value
+            )?;
+            for (index, line) in lines.enumerate() {
+                let offset = entry.offset + (index + 1) * BYTES_PER_LINE;
+                writeln!(f, "{}{:08x}  {}", indent, offset, hex(line).trim_end())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Format bytes as space separated hex padded to a full line.
+fn hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(BYTES_PER_LINE * 3);
+    for byte in bytes {
+        let _ = write!(hex, "{:02x} ", byte);
+    }
+    format!("{:width$}", hex.trim_end(), width = BYTES_PER_LINE * 3 - 1)
+}
+
+/// State of a trace that is being recorded.
+pub(crate) struct Tracer {
+    trace: Trace,
+    /// Number of traced reads in progress, reads made by
+    /// other reads are included in the outermost entry.
+    depth: usize,
+    /// Bytes consumed by the outermost read in progress.
+    bytes: Vec<u8>,
+}
+
+impl<'a> BinaryReader<'a> {
+    /// Start recording a trace of every value read.
+    ///
+    /// Any trace already being recorded is discarded.
+    pub fn start_trace(&mut self) {
+        self.tracer = Some(Box::new(Tracer {
+            trace: Trace::default(),
+            depth: 0,
+            bytes: Vec::new(),
+        }));
+    }
+
+    /// Get the trace being recorded.
+    pub fn trace(&self) -> Option<&Trace> {
+        self.tracer.as_ref().map(|tracer| &tracer.trace)
+    }
+
+    /// Stop recording and return the trace.
+    pub fn take_trace(&mut self) -> Option<Trace> {
+        self.tracer.take().map(|tracer| tracer.trace)
+    }
+
+    /// Fill a buffer from the stream, recording the bytes if tracing.
+    pub(crate) fn fill(&mut self, buffer: &mut [u8]) -> Result<()> {
+        self.stream.read_exact(buffer)?;
+        self.record(buffer);
+        Ok(())
+    }
+
+    /// Record bytes read from the stream if tracing.
+    pub(crate) fn record(&mut self, bytes: &[u8]) {
+        if let Some(tracer) = &mut self.tracer {
+            if tracer.depth > 0 {
+                tracer.bytes.extend_from_slice(bytes);
+            }
+        }
+    }
+
+    /// Run a read, recording it in the trace if tracing.
+    pub(crate) fn traced<T, F>(&mut self, method: &'static str, read: F) -> Result<T>
+    where
+        T: Debug,
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        if self.tracer.is_none() {
+            return read(self);
+        }
+        let offset = self.stream.tell()?;
+        if let Some(tracer) = &mut self.tracer {
+            tracer.depth += 1;
+        }
+        let result = read(self);
+        let outermost = self.tracer.as_ref().is_some_and(|tracer| tracer.depth == 1);
+        let scope = if outermost {
+            self.path()
+        } else {
+            String::new()
+        };
+        if let Some(tracer) = &mut self.tracer {
+            tracer.depth = tracer.depth.saturating_sub(1);
+            if tracer.depth == 0 {
+                let bytes = std::mem::take(&mut tracer.bytes);
+                if let Ok(value) = &result {
+                    tracer.trace.entries.push(TraceEntry {
+                        method,
+                        offset,
+                        bytes,
+                        value: format!("{:?}", value),
+                        scope,
+                    });
+                }
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,60 @@
+//! Versioned encoding with upgrades from older versions.
+use crate::{BinaryError, BinaryReader, BinaryWriter, Decode, Encode, Result};
+
+/// Trait for types whose encoding changes between versions.
+///
+/// `write_versioned()` writes a version tag followed by the current
+/// encoding. `read_versioned()` reads the tag and decodes the current
+/// version with `Decode`, older versions are decoded with
+/// `decode_version()` and then brought up to date by calling
+/// `upgrade()` once for every version in between.
+pub trait Versioned: Encode + Decode + Default {
+    /// Current version written by `write_versioned()`.
+    const VERSION: u32;
+
+    /// Decode data written by an older version into self.
+    ///
+    /// The default implementation rejects every older version.
+    fn decode_version(&mut self, _reader: &mut BinaryReader, version: u32) -> Result<()> {
+        Err(BinaryError::UnsupportedVersion(version))
+    }
+
+    /// Upgrade a value decoded as `version` to `version + 1`.
+    fn upgrade(&mut self, _version: u32) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> BinaryReader<'a> {
+    /// Read a version tag and decode a value of that version,
+    /// upgrading it to the current version.
+    pub fn read_versioned<T: Versioned>(&mut self) -> Result<T> {
+        let version = self.read_7bit_encoded_u32()?;
+        if version > T::VERSION {
+            return Err(BinaryError::VersionTooNew {
+                version,
+                supported: T::VERSION,
+            });
+        }
+        let mut value = T::default();
+        if version == T::VERSION {
+            value.decode(self)?;
+        } else {
+            value.decode_version(self, version)?;
+            for version in version..T::VERSION {
+                value.upgrade(version)?;
+            }
+        }
+        Ok(value)
+    }
+}
+
+impl<'a> BinaryWriter<'a> {
+    /// Write the current version tag followed by the value.
+    ///
+    /// The tag is 7bit encoded.
+    pub fn write_versioned<T: Versioned>(&mut self, value: &T) -> Result<()> {
+        self.write_7bit_encoded_u32(T::VERSION)?;
+        value.encode(self)
+    }
+}
@@ -1,6 +1,10 @@
 use anyhow::Result;
 use binary_rw::{
-    BinaryReader, BinaryWriter, Endian, FileStream, MemoryStream, SeekStream, SliceStream,
+    Adler32, AtomicFile, Be, BinaryError, BinaryReader, BinaryWriter, Checksum, ChecksumStream,
+    Crc32, Crc32c, Decode, Encode, Endian, F64Le, FileStream, FixedSize, Framing, HasherChecksum,
+    I16Be, IoStream, Le, LengthPrefix, Limits, MemoryStream, OffsetKind, OffsetWidth, RecordFile,
+    RecordLog, SeekPastEnd, SeekStream, SliceStream, SliceStreamMut, SyncPolicy, TraceEntry, U32Le,
+    Versioned,
 };
 
 fn create_writer_stream(name: &str) -> FileStream {
@@ -19,6 +23,7 @@ fn cleanup(name: &str) {
 }
 
 #[test]
+#[allow(clippy::needless_borrows_for_generic_args)]
 fn borrow_test() -> Result<()> {
     let mut stream = MemoryStream::new();
     let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
@@ -160,6 +165,7 @@ fn slice_test() -> Result<()> {
 }
 
 #[test]
+#[allow(clippy::unnecessary_to_owned)]
 fn seek_test() -> Result<()> {
     let temp: f32 = 50.0;
     let seek_loc = 5;
@@ -663,6 +669,7 @@ fn read_out_of_range() -> Result<()> {
 }
 
 #[test]
+#[allow(clippy::unnecessary_to_owned)]
 fn read_write_string() -> Result<()> {
     let temp = "Hello World";
     let mut stream = create_writer_stream("read_write_string");
@@ -679,6 +686,7 @@ fn read_write_string() -> Result<()> {
 }
 
 #[test]
+#[allow(clippy::unnecessary_to_owned)]
 fn read_write_7bit_encoded_string() -> Result<()> {
     let temp = "Hello World";
     let mut stream = create_writer_stream("read_7bit_encoded_len_string");
@@ -831,3 +839,1962 @@ fn write_to_filestream_overlapping() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn reserve_and_fill_placeholder() -> Result<()> {
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    let count = writer.reserve::<u32>()?;
+    writer.write_u8(1)?;
+    writer.write_u8(2)?;
+    writer.fill(count, 2)?;
+    assert_eq!(6, writer.tell()?);
+
+    let buffer: Vec<u8> = stream.into();
+    assert_eq!(vec![0, 0, 0, 2, 1, 2], buffer);
+    Ok(())
+}
+
+#[test]
+fn write_length_prefixed_block() -> Result<()> {
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Little);
+    let written = writer.write_length_prefixed(LengthPrefix::U16, |w| {
+        w.write_u32(7)?;
+        w.write_length_prefixed(LengthPrefix::Varint, |w| {
+            w.write_u8(9)?;
+            Ok(())
+        })?;
+        Ok(())
+    })?;
+    assert_eq!(8, written);
+
+    let buffer: Vec<u8> = stream.into();
+    assert_eq!(vec![6, 0, 7, 0, 0, 0, 1, 9], buffer);
+
+    let mut stream = SliceStream::new(&buffer);
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+    assert_eq!(6, reader.read_u16()?);
+    assert_eq!(7, reader.read_u32()?);
+    assert_eq!(1, reader.read_7bit_encoded_usize()?);
+    assert_eq!(9, reader.read_u8()?);
+    Ok(())
+}
+
+#[test]
+fn write_length_prefixed_overflow() -> Result<()> {
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    let result = writer.write_length_prefixed(LengthPrefix::U16, |w| {
+        w.write_bytes_with_value(u16::MAX as usize + 1, 0)?;
+        Ok(())
+    });
+    assert!(result.is_err());
+
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    let result = writer.write_length_prefixed(LengthPrefix::U32, |w| {
+        w.seek(0)?;
+        Ok(())
+    });
+    assert!(matches!(
+        result,
+        Err(BinaryError::BlockUnderflow { start: 4, end: 0 })
+    ));
+    Ok(())
+}
+
+#[test]
+fn labels_in_varint_block() -> Result<()> {
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_u8(0xAA)?;
+    writer.write_length_prefixed(LengthPrefix::Varint, |w| {
+        w.write_label_ref("end", OffsetKind::Absolute, OffsetWidth::U8)?;
+        w.define_label("inner")?;
+        w.write_u8(0xBB)?;
+        Ok(())
+    })?;
+    writer.write_label_ref("inner", OffsetKind::Absolute, OffsetWidth::U8)?;
+    writer.define_label("end")?;
+    assert_eq!(Some(3), writer.label_position("inner"));
+    writer.finish()?;
+
+    let buffer: Vec<u8> = stream.into();
+    assert_eq!(vec![0xAA, 2, 5, 0xBB, 3], buffer);
+
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_length_prefixed(LengthPrefix::Varint, |w| {
+        w.write_label_ref("missing", OffsetKind::Absolute, OffsetWidth::U32)?;
+        Ok(())
+    })?;
+    assert!(matches!(
+        writer.finish(),
+        Err(BinaryError::UndefinedLabel(label)) if label == "missing"
+    ));
+
+    writer.define_label("twice")?;
+    let result = writer.write_length_prefixed(LengthPrefix::Varint, |w| w.define_label("twice"));
+    assert!(matches!(result, Err(BinaryError::DuplicateLabel(_))));
+    Ok(())
+}
+
+#[test]
+fn resolve_label_references() -> Result<()> {
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_label_ref("data", OffsetKind::Absolute, OffsetWidth::U32)?;
+    writer.write_label_ref("data", OffsetKind::Relative, OffsetWidth::U8)?;
+    writer.write_u8(0xff)?;
+    writer.define_label("data")?;
+    writer.write_u16(42)?;
+    writer.finish()?;
+    assert_eq!(Some(6), writer.label_position("data"));
+    assert_eq!(8, writer.tell()?);
+
+    let buffer: Vec<u8> = stream.into();
+    assert_eq!(vec![0, 0, 0, 6, 2, 0xff, 0, 42], buffer);
+    Ok(())
+}
+
+#[test]
+fn label_errors() -> Result<()> {
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_label_ref("missing", OffsetKind::Absolute, OffsetWidth::U16)?;
+    assert!(matches!(
+        writer.finish(),
+        Err(BinaryError::UndefinedLabel(label)) if label == "missing"
+    ));
+    writer.define_label("missing")?;
+    assert!(matches!(
+        writer.define_label("missing"),
+        Err(BinaryError::DuplicateLabel(_))
+    ));
+    writer.finish()?;
+
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_u8(0)?;
+    writer.define_label("early")?;
+    writer.write_u8(0)?;
+    writer.write_label_ref("early", OffsetKind::Relative, OffsetWidth::U16)?;
+    assert!(matches!(
+        writer.finish(),
+        Err(BinaryError::LabelOverflow(_))
+    ));
+
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_label_ref("far", OffsetKind::Absolute, OffsetWidth::U8)?;
+    writer.write_bytes_with_value(256, 0)?;
+    writer.define_label("far")?;
+    assert!(matches!(
+        writer.finish(),
+        Err(BinaryError::LabelOverflow(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn read_at_offset_returns() -> Result<()> {
+    let buffer: Vec<u8> = vec![0, 0, 0, 6, 0xff, 0xff, 0, 0, 0, 42, 0, 7];
+    let mut stream = SliceStream::new(&buffer);
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+
+    let offset = reader.read_u32()? as usize;
+    let value = reader.at(offset, |r| r.read_u32())?;
+    assert_eq!(42, value);
+    assert_eq!(4, reader.tell()?);
+
+    assert!(reader.at(10, |r| r.read_u32()).is_err());
+    assert_eq!(4, reader.tell()?);
+
+    reader.set_base(8);
+    assert_eq!(8, reader.base());
+    assert_eq!(7, reader.at(2, |r| r.read_u16())?);
+    assert_eq!(4, reader.tell()?);
+
+    assert!(matches!(
+        reader.at(usize::MAX, |r| r.read_u8()),
+        Err(BinaryError::OffsetOverflow {
+            base: 8,
+            offset: usize::MAX
+        })
+    ));
+    assert_eq!(4, reader.tell()?);
+    Ok(())
+}
+
+#[test]
+fn push_pop_position() -> Result<()> {
+    let buffer: Vec<u8> = vec![1, 2, 3, 4];
+    let mut stream = SliceStream::new(&buffer);
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+
+    reader.read_u8()?;
+    reader.push_position()?;
+    reader.seek(3)?;
+    reader.push_position()?;
+    assert_eq!(4, reader.read_u8()?);
+    assert_eq!(3, reader.pop_position()?);
+    assert_eq!(1, reader.pop_position()?);
+    assert_eq!(2, reader.read_u8()?);
+    assert!(matches!(
+        reader.pop_position(),
+        Err(BinaryError::PositionStackEmpty)
+    ));
+    Ok(())
+}
+
+#[test]
+fn checksum_known_values() {
+    let mut crc = Crc32::new();
+    crc.update(b"123456789");
+    assert_eq!(0xCBF43926, crc.digest());
+    crc.reset();
+    crc.update(b"1234");
+    crc.update(b"56789");
+    assert_eq!(0xCBF43926, crc.digest());
+
+    let mut crc = Crc32c::new();
+    crc.update(b"123456789");
+    assert_eq!(0xE3069283, crc.digest());
+
+    let mut adler = Adler32::new();
+    adler.update(b"Wikipedia");
+    assert_eq!(0x11E60398, adler.digest());
+}
+
+#[test]
+fn checksum_stream_trailers() -> Result<()> {
+    let mut stream = ChecksumStream::new(MemoryStream::new(), Crc32::new());
+    for value in [1u32, 2] {
+        let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+        writer.write_u32(value)?;
+        writer.write_string("record")?;
+        stream.write_trailer(Endian::Big)?;
+    }
+    let buffer: Vec<u8> = stream.into_inner().into();
+
+    let mut stream = ChecksumStream::new(SliceStream::new(&buffer), Crc32::new());
+    for value in [1u32, 2] {
+        let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+        assert_eq!(value, reader.read_u32()?);
+        assert_eq!("record", reader.read_string()?);
+        stream.verify_trailer(Endian::Big)?;
+    }
+
+    let mut corrupted = buffer.clone();
+    corrupted[3] ^= 1;
+    let mut stream = ChecksumStream::new(SliceStream::new(&corrupted), Crc32::new());
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    reader.read_u32()?;
+    reader.read_string()?;
+    assert!(matches!(
+        stream.verify_trailer(Endian::Big),
+        Err(BinaryError::ChecksumMismatch { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn checksum_stream_rejects_backpatching() -> Result<()> {
+    let mut stream = ChecksumStream::new(MemoryStream::new(), Crc32::new());
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_u8(1)?;
+    assert_eq!(1, writer.seek(1)?);
+    assert!(matches!(writer.seek(0), Err(BinaryError::Unsupported(_))));
+    let result = writer.write_length_prefixed(LengthPrefix::U32, |w| {
+        w.write_u8(2)?;
+        Ok(())
+    });
+    assert!(matches!(result, Err(BinaryError::Unsupported(_))));
+
+    // Varint blocks are buffered so they do not seek
+    let mut stream = ChecksumStream::new(MemoryStream::new(), Crc32::new());
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_length_prefixed(LengthPrefix::Varint, |w| {
+        w.write_u8(2)?;
+        Ok(())
+    })?;
+    let mut crc = Crc32::new();
+    crc.update(&[1, 2]);
+    assert_eq!(crc.digest(), stream.digest());
+    Ok(())
+}
+
+#[test]
+fn checksum_stream_hasher() -> Result<()> {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
+
+    let mut stream =
+        ChecksumStream::new(MemoryStream::new(), HasherChecksum::<DefaultHasher>::new());
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Little);
+    writer.write_bytes(b"payload")?;
+
+    let mut hasher = DefaultHasher::new();
+    hasher.write(b"payload");
+    assert_eq!(hasher.finish(), stream.digest());
+    assert_eq!(8, stream.write_trailer(Endian::Little)?);
+    assert_eq!(15, stream.len()?);
+    Ok(())
+}
+
+#[cfg(any(feature = "deflate", feature = "zstd"))]
+fn compressed_section_round_trip(codec: binary_rw::Codec) -> Result<()> {
+    use binary_rw::{CompressReader, CompressWriter};
+
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_u32(0xCAFE)?;
+
+    let mut compressed = CompressWriter::new(&mut stream, codec)?;
+    let mut writer = BinaryWriter::new(&mut compressed, Endian::Big);
+    for value in 0..1000u32 {
+        writer.write_u32(value)?;
+    }
+    assert_eq!(4000, writer.tell()?);
+    writer.write_string("section")?;
+    compressed.finish()?;
+
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_u32(0xBEEF)?;
+    let compressed_len = writer.len()? - 8;
+    assert!(compressed_len < 4000);
+
+    stream.seek(0)?;
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    assert_eq!(0xCAFE, reader.read_u32()?);
+
+    let mut decompressed = CompressReader::new(&mut stream, codec)?;
+    let mut reader = BinaryReader::new(&mut decompressed, Endian::Big);
+    for value in 0..1000u32 {
+        assert_eq!(value, reader.read_u32()?);
+    }
+    assert_eq!(4000, reader.tell()?);
+    assert_eq!("section", reader.read_string()?);
+    decompressed.finish()?;
+    assert_eq!(4 + compressed_len, stream.tell()?);
+
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    assert_eq!(0xBEEF, reader.read_u32()?);
+    Ok(())
+}
+
+#[cfg(feature = "deflate")]
+#[test]
+fn deflate_sections() -> Result<()> {
+    compressed_section_round_trip(binary_rw::Codec::Deflate)?;
+    compressed_section_round_trip(binary_rw::Codec::Zlib)?;
+    compressed_section_round_trip(binary_rw::Codec::Gzip)?;
+    Ok(())
+}
+
+#[cfg(feature = "deflate")]
+#[test]
+fn deflate_section_unseekable() -> Result<()> {
+    use binary_rw::{Codec, CompressReader, CompressWriter};
+
+    let mut stream = MemoryStream::new();
+    let mut compressed = CompressWriter::new(&mut stream, Codec::Deflate)?;
+    let mut writer = BinaryWriter::new(&mut compressed, Endian::Big);
+    for value in 0..1000u32 {
+        writer.write_u32(value)?;
+    }
+    compressed.finish()?;
+    let buffer: Vec<u8> = stream.into();
+
+    let mut stream = IoStream::new(buffer.as_slice());
+    let mut decompressed = CompressReader::new(&mut stream, Codec::Deflate)?;
+    let mut reader = BinaryReader::new(&mut decompressed, Endian::Big);
+    for value in 0..1000u32 {
+        assert_eq!(value, reader.read_u32()?);
+    }
+    decompressed.finish()?;
+    assert_eq!(buffer.len(), stream.tell()?);
+    Ok(())
+}
+
+#[cfg(feature = "deflate")]
+#[test]
+fn deflate_section_large_write() -> Result<()> {
+    use binary_rw::{Codec, CompressReader, CompressWriter};
+
+    // Incompressible data so the encoder cannot take it in one call
+    let mut seed = 1u32;
+    let data: Vec<u8> = (0..1 << 20)
+        .map(|_| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 24) as u8
+        })
+        .collect();
+
+    let mut stream = MemoryStream::new();
+    let mut compressed = CompressWriter::new(&mut stream, Codec::Deflate)?;
+    let mut writer = BinaryWriter::new(&mut compressed, Endian::Big);
+    assert_eq!(data.len(), writer.write_bytes(&data)?);
+    assert_eq!(data.len(), compressed.position());
+    compressed.finish()?;
+
+    stream.seek(0)?;
+    let mut decompressed = CompressReader::new(&mut stream, Codec::Deflate)?;
+    let mut reader = BinaryReader::new(&mut decompressed, Endian::Big);
+    assert_eq!(data, reader.read_bytes(data.len())?);
+    Ok(())
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd_section() -> Result<()> {
+    compressed_section_round_trip(binary_rw::Codec::Zstd)
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn read_write_mmap() -> Result<()> {
+    use binary_rw::{MmapStream, MmapStreamMut};
+    use std::io::Write;
+
+    let name = "mmap.test";
+    let mut stream = unsafe { MmapStreamMut::create(name, 12)? };
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Little);
+    writer.write_u32(42)?;
+    writer.write_f64(1.5)?;
+    assert!(writer.write_u8(0).is_err());
+    stream.flush()?;
+    drop(stream);
+
+    let mut stream = unsafe { MmapStream::open(name)? };
+    assert_eq!(12, stream.len()?);
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+    assert_eq!(42, reader.read_u32()?);
+    assert_eq!(1.5, reader.read_f64()?);
+    assert!(reader.read_u8().is_err());
+
+    stream.seek(0)?;
+    assert_eq!(&[42, 0, 0, 0], stream.read_slice(4)?);
+    assert_eq!(4, stream.tell()?);
+    assert!(stream.read_slice(9).is_err());
+    assert_eq!(12, stream.as_slice().len());
+    drop(stream);
+
+    cleanup("mmap");
+    Ok(())
+}
+
+#[test]
+fn filestream_tracks_length() -> Result<()> {
+    drop(create_writer_stream("filestream_length"));
+    let mut stream = FileStream::write("filestream_length.test")?;
+    assert_eq!(0, stream.len()?);
+
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_u32(1)?;
+    writer.write_u32(2)?;
+    assert_eq!(8, writer.len()?);
+
+    writer.seek(4)?;
+    writer.write_u16(3)?;
+    assert_eq!(8, writer.len()?);
+
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    reader.seek(0)?;
+    assert_eq!(1, reader.read_u32()?);
+    assert_eq!(3, reader.read_u16()?);
+    assert_eq!(2, reader.read_u16()?);
+    assert!(reader.read_u8().is_err());
+
+    cleanup("filestream_length");
+    Ok(())
+}
+
+#[test]
+fn buffered_filestream_interleaves() -> Result<()> {
+    drop(create_writer_stream("filestream_buffered"));
+    let mut stream = FileStream::write("filestream_buffered.test")?.buffered(16);
+
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Little);
+    for value in 0..10u32 {
+        writer.write_u32(value)?;
+    }
+    assert_eq!(40, writer.len()?);
+
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+    reader.seek(8)?;
+    assert_eq!(2, reader.read_u32()?);
+
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Little);
+    writer.seek(12)?;
+    writer.write_u32(30)?;
+    writer.seek(40)?;
+    writer.write_bytes([0xff; 32])?;
+    assert_eq!(72, writer.len()?);
+
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+    reader.seek(0)?;
+    for value in [0, 1, 2, 30, 4, 5, 6, 7, 8, 9] {
+        assert_eq!(value, reader.read_u32()?);
+    }
+    assert_eq!(vec![0xff; 32], reader.read_bytes(32)?);
+
+    stream.sync_all()?;
+    assert_eq!(72, stream.metadata()?.len());
+    drop(stream);
+
+    cleanup("filestream_buffered");
+    Ok(())
+}
+
+#[test]
+fn memorystream_buffer_management() -> Result<()> {
+    let mut stream = MemoryStream::with_capacity(64);
+    assert!(stream.capacity() >= 64);
+
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_u32(0x01020304)?;
+    writer.write_u32(0x05060708)?;
+    assert_eq!(&[1, 2, 3, 4, 5, 6, 7, 8], stream.as_slice());
+
+    stream.as_mut_slice()[0] = 9;
+    stream.truncate(6);
+    assert_eq!(6, stream.tell()?);
+    assert_eq!(&[9, 2, 3, 4, 5, 6], stream.as_slice());
+
+    stream.set_len(8);
+    assert_eq!(&[9, 2, 3, 4, 5, 6, 0, 0], stream.as_slice());
+    stream.set_len(2);
+    assert_eq!(2, stream.tell()?);
+
+    let capacity = stream.capacity();
+    stream.clear();
+    assert_eq!(0, stream.len()?);
+    assert_eq!(0, stream.tell()?);
+    assert_eq!(capacity, stream.capacity());
+
+    stream.reserve(128);
+    assert!(stream.capacity() >= 128);
+    stream.shrink_to_fit();
+    assert!(stream.capacity() < 128);
+
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_u16(1)?;
+    writer.seek(1)?;
+    writer.write_u16(0x0203)?;
+    assert_eq!(&[0, 2, 3], stream.as_slice());
+
+    let buffer = [1u8, 2, 3];
+    assert_eq!(&buffer, SliceStream::new(&buffer).as_slice());
+    Ok(())
+}
+
+#[test]
+fn write_to_mutable_slice() -> Result<()> {
+    let mut buffer = [0u8; 6];
+    let mut stream = SliceStreamMut::new(&mut buffer);
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_u32(0x01020304)?;
+    assert!(matches!(
+        writer.write_u32(5),
+        Err(BinaryError::WritePastEnd)
+    ));
+    writer.write_u16(0x0506)?;
+    assert!(matches!(writer.write_u8(7), Err(BinaryError::WritePastEnd)));
+    assert_eq!(0, stream.remaining());
+
+    stream.seek(2)?;
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    assert_eq!(0x0304, reader.read_u16()?);
+    assert!(matches!(
+        reader.read_u32(),
+        Err(BinaryError::ReadPastEof { .. })
+    ));
+
+    assert_eq!([1, 2, 3, 4, 5, 6], buffer);
+    Ok(())
+}
+
+#[test]
+fn seek_past_end_memorystream() -> Result<()> {
+    let mut stream = MemoryStream::new();
+    assert_eq!(SeekPastEnd::ZeroFill, stream.seek_past_end());
+    stream.seek(3)?;
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    assert!(reader.read_u8().is_err());
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_u8(1)?;
+    assert_eq!(&[0, 0, 0, 1], stream.as_slice());
+    stream.seek(6)?;
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    assert_eq!(0, writer.write_bytes([])?);
+    assert_eq!(&[0, 0, 0, 1], stream.as_slice());
+    stream.seek(4)?;
+
+    stream.set_seek_past_end(SeekPastEnd::Error);
+    assert!(matches!(
+        stream.seek(5),
+        Err(BinaryError::SeekOutOfBounds {
+            position: 5,
+            length: 4
+        })
+    ));
+    assert_eq!(4, stream.tell()?);
+    stream.seek(4)?;
+    Ok(())
+}
+
+#[test]
+fn seek_past_end_filestream() -> Result<()> {
+    let mut stream = create_writer_stream("seek_past_end");
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_u8(1)?;
+    writer.seek(3)?;
+    writer.write_u8(2)?;
+    assert_eq!(4, writer.len()?);
+
+    // An empty write past the end does not extend the file
+    writer.seek(6)?;
+    assert_eq!(0, writer.write_bytes([])?);
+    assert_eq!(4, writer.len()?);
+
+    stream.set_seek_past_end(SeekPastEnd::Error);
+    assert!(matches!(
+        stream.seek(5),
+        Err(BinaryError::SeekOutOfBounds { .. })
+    ));
+    drop(stream);
+
+    let mut stream = create_reader_stream("seek_past_end");
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    assert_eq!(4, reader.len()?);
+    assert_eq!(vec![1, 0, 0, 2], reader.read_bytes(4)?);
+
+    cleanup("seek_past_end");
+    Ok(())
+}
+
+#[test]
+fn seek_past_end_slices() -> Result<()> {
+    let buffer = [1u8, 2];
+    let mut stream = SliceStream::new(&buffer);
+    stream.seek(4)?;
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    assert!(matches!(
+        reader.read_u8(),
+        Err(BinaryError::ReadPastEof { .. })
+    ));
+    stream.set_seek_past_end(SeekPastEnd::Error);
+    assert!(matches!(
+        stream.seek(3),
+        Err(BinaryError::SeekOutOfBounds { .. })
+    ));
+
+    let mut buffer = [0u8; 2];
+    let mut stream = SliceStreamMut::new(&mut buffer);
+    stream.seek(4)?;
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    assert!(matches!(writer.write_u8(1), Err(BinaryError::WritePastEnd)));
+    stream.set_seek_past_end(SeekPastEnd::Error);
+    assert!(matches!(
+        stream.seek(3),
+        Err(BinaryError::SeekOutOfBounds { .. })
+    ));
+    stream.seek(2)?;
+    Ok(())
+}
+
+#[test]
+fn seek_near_usize_max() -> Result<()> {
+    let mut stream = MemoryStream::new();
+    stream.seek(usize::MAX - 1)?;
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    assert!(matches!(
+        writer.write_u16(1),
+        Err(BinaryError::WritePastEnd)
+    ));
+    assert!(writer.write_u8(1).is_err());
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    assert!(matches!(
+        reader.read_u16(),
+        Err(BinaryError::ReadPastEof { available: 0, .. })
+    ));
+    assert!(stream.as_slice().is_empty());
+
+    let buffer = [1u8, 2];
+    let mut stream = SliceStream::new(&buffer);
+    stream.seek(usize::MAX - 1)?;
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    assert!(matches!(
+        reader.read_u32(),
+        Err(BinaryError::ReadPastEof { .. })
+    ));
+
+    let mut buffer = [0u8; 2];
+    let mut stream = SliceStreamMut::new(&mut buffer);
+    stream.seek(usize::MAX - 1)?;
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    assert!(matches!(
+        writer.write_u32(1),
+        Err(BinaryError::WritePastEnd)
+    ));
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    assert!(matches!(
+        reader.read_u32(),
+        Err(BinaryError::ReadPastEof { .. })
+    ));
+
+    let mut stream = create_writer_stream("seek_near_usize_max").buffered(16);
+    stream.seek(usize::MAX - 1)?;
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    assert!(matches!(
+        writer.write_u32(1),
+        Err(BinaryError::WritePastEnd)
+    ));
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    assert!(matches!(
+        reader.read_u32(),
+        Err(BinaryError::ReadPastEof { .. })
+    ));
+    drop(stream);
+
+    cleanup("seek_near_usize_max");
+    Ok(())
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn seek_past_end_mmap() -> Result<()> {
+    use binary_rw::{MmapStream, MmapStreamMut};
+
+    let name = "seek_past_end_mmap.test";
+    let mut stream = unsafe { MmapStreamMut::create(name, 2)? };
+    stream.seek(4)?;
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    assert!(matches!(writer.write_u8(1), Err(BinaryError::WritePastEnd)));
+    stream.set_seek_past_end(SeekPastEnd::Error);
+    assert!(stream.seek(3).is_err());
+    drop(stream);
+
+    let mut stream = unsafe { MmapStream::open(name)? };
+    stream.seek(4)?;
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    assert!(matches!(
+        reader.read_u8(),
+        Err(BinaryError::ReadPastEof { .. })
+    ));
+    stream.set_seek_past_end(SeekPastEnd::Error);
+    assert!(stream.seek(3).is_err());
+    drop(stream);
+
+    cleanup("seek_past_end_mmap");
+    Ok(())
+}
+
+#[test]
+fn read_past_eof_context() -> Result<()> {
+    let buffer = [0u8; 6];
+    let mut stream = SliceStream::new(&buffer);
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    reader.read_u32()?;
+    assert!(matches!(
+        reader.read_u32(),
+        Err(BinaryError::ReadPastEof {
+            offset: 4,
+            requested: 4,
+            available: 2
+        })
+    ));
+    Ok(())
+}
+
+#[test]
+fn decode_error_field_path() -> Result<()> {
+    let buffer = [0u8, 2, 0, 1, 0, 0, 0, 0xAA];
+    let mut stream = SliceStream::new(&buffer);
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+
+    let error = reader
+        .field("header", |r| {
+            let count = r.field("count", |r| r.read_u16())?;
+            for index in 0..count as usize {
+                r.field("entries", |r| {
+                    r.element(index, |r| r.field("name", |r| r.read_u32()))
+                })?;
+            }
+            Ok(())
+        })
+        .unwrap_err();
+
+    assert!(matches!(
+        error,
+        BinaryError::Context { ref path, offset: 6, .. } if path == "header.entries[1].name"
+    ));
+    assert!(matches!(error.root(), BinaryError::ReadPastEof { .. }));
+    assert_eq!(
+        "header.entries[1].name at offset 0x6: \
+         attempt to read 4 bytes past EOF at offset 0x6, 2 available",
+        error.to_string()
+    );
+    assert_eq!("", reader.path());
+    Ok(())
+}
+
+#[test]
+fn allocation_limits() -> Result<()> {
+    // Length prefix claiming far more bytes than the stream holds.
+    let buffer = [0xFF, 0xFF, 0xFF, 0xFF, b'a', b'b', b'c'];
+    let mut stream = SliceStream::new(&buffer);
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+    let length = reader.read_u32()? as usize;
+    assert!(matches!(
+        reader.read_bytes(length),
+        Err(BinaryError::ReadPastEof {
+            offset: 4,
+            requested: 0xFFFF_FFFF,
+            available: 3
+        })
+    ));
+
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Little);
+    writer.write_string("hello")?;
+    writer.write_string("world")?;
+
+    stream.seek(0)?;
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+    reader.set_limits(Limits {
+        max_allocation: 4,
+        ..Default::default()
+    });
+    assert!(matches!(
+        reader.read_string(),
+        Err(BinaryError::AllocationLimit {
+            requested: 5,
+            limit: 4
+        })
+    ));
+
+    reader.seek(0)?;
+    reader.set_limits(Limits {
+        max_total_allocation: 8,
+        ..Default::default()
+    });
+    assert_eq!("hello", reader.read_string()?);
+    assert_eq!(5, reader.allocated());
+    let second = reader.tell()?;
+    assert!(matches!(
+        reader.read_string(),
+        Err(BinaryError::AllocationLimit {
+            requested: 10,
+            limit: 8
+        })
+    ));
+    reader.reset_allocated();
+    reader.seek(second)?;
+    assert_eq!("world", reader.read_string()?);
+
+    reader.set_limits(Limits {
+        max_elements: 2,
+        ..Default::default()
+    });
+    reader.check_elements(2)?;
+    assert!(matches!(
+        reader.check_elements(3),
+        Err(BinaryError::ElementLimit { count: 3, limit: 2 })
+    ));
+    Ok(())
+}
+
+#[test]
+fn read_7bit_encoded_overflow() -> Result<()> {
+    // Continuation bits past the width of the length are rejected
+    let buffer = [0xFF; 12];
+    let mut stream = SliceStream::new(&buffer);
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+    assert!(matches!(
+        reader.read_7bit_encoded_len_string(),
+        Err(BinaryError::VarintTooLong { .. })
+    ));
+
+    reader.seek(0)?;
+    assert!(matches!(
+        reader.read_7bit_encoded_u32(),
+        Err(BinaryError::VarintTooLong { bits: 32 })
+    ));
+    reader.seek(0)?;
+    assert!(matches!(
+        reader.read_7bit_encoded_i64(),
+        Err(BinaryError::VarintTooLong { bits: 64 })
+    ));
+
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Little);
+    writer.write_7bit_encoded_u64(u64::MAX)?;
+    writer.write_7bit_encoded_i32(-1)?;
+    stream.seek(0)?;
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+    assert_eq!(u64::MAX, reader.read_7bit_encoded_u64()?);
+    assert_eq!(-1, reader.read_7bit_encoded_i32()?);
+    Ok(())
+}
+
+#[test]
+fn read_write_slices() -> Result<()> {
+    let floats: Vec<f32> = (0..3000).map(|i| i as f32 * 0.5).collect();
+    let shorts = [-1i16, 2, -300];
+
+    for big in [false, true] {
+        let endian = || if big { Endian::Big } else { Endian::Little };
+        let mut bulk = MemoryStream::new();
+        let mut writer = BinaryWriter::new(&mut bulk, endian());
+        assert_eq!(12000, writer.write_slice(&floats)?);
+        assert_eq!(6, writer.write_slice(&shorts)?);
+
+        let mut single = MemoryStream::new();
+        let mut writer = BinaryWriter::new(&mut single, endian());
+        for value in &floats {
+            writer.write_f32(value)?;
+        }
+        for value in &shorts {
+            writer.write_i16(value)?;
+        }
+        assert_eq!(single.as_slice(), bulk.as_slice());
+
+        bulk.seek(0)?;
+        let mut reader = BinaryReader::new(&mut bulk, endian());
+        let mut read_floats = vec![0f32; floats.len()];
+        reader.read_into_slice(&mut read_floats)?;
+        assert_eq!(floats, read_floats);
+        assert_eq!(shorts.to_vec(), reader.read_vec::<i16>(3)?);
+    }
+
+    let buffer = [1u8, 2, 3, 4, 5, 6, 7, 8];
+    let mut stream = SliceStream::new(&buffer);
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    assert_eq!(vec![0x0102_0304u32, 0x0506_0708], reader.read_vec(2)?);
+
+    reader.seek(0)?;
+    assert!(matches!(
+        reader.read_vec::<u64>(usize::MAX),
+        Err(BinaryError::ReadPastEof { .. })
+    ));
+    reader.set_limits(Limits {
+        max_elements: 1,
+        ..Default::default()
+    });
+    assert!(matches!(
+        reader.read_vec::<u32>(2),
+        Err(BinaryError::ElementLimit { count: 2, limit: 1 })
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+mod serde_types {
+    use serde::{Deserialize, Serialize};
+    pub use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    pub enum Shape {
+        Empty,
+        Circle(f32),
+        Line(u8, u8),
+        Rect { width: u16, height: u16 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Entry {
+        pub name: String,
+        pub id: u64,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Scene {
+        pub title: String,
+        pub shapes: Vec<Shape>,
+        pub entries: Vec<Entry>,
+        pub tags: BTreeMap<String, i32>,
+        pub parent: Option<Box<Scene>>,
+        pub flag: bool,
+        pub letter: char,
+        pub pair: (i128, u16),
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() -> Result<()> {
+    use binary_rw::{from_slice, to_vec, LengthEncoding};
+    use serde_types::*;
+
+    let scene = Scene {
+        title: "scene".to_string(),
+        shapes: vec![
+            Shape::Empty,
+            Shape::Circle(1.5),
+            Shape::Line(1, 2),
+            Shape::Rect {
+                width: 3,
+                height: 4,
+            },
+        ],
+        entries: vec![Entry {
+            name: "first".to_string(),
+            id: 7,
+        }],
+        tags: [("a".to_string(), -1), ("b".to_string(), 2)].into(),
+        parent: Some(Box::new(Scene {
+            title: "parent".to_string(),
+            shapes: Vec::new(),
+            entries: Vec::new(),
+            tags: BTreeMap::new(),
+            parent: None,
+            flag: false,
+            letter: 'p',
+            pair: (0, 0),
+        })),
+        flag: true,
+        letter: 'é',
+        pair: (-5, 9),
+    };
+
+    for lengths in [LengthEncoding::Fixed, LengthEncoding::Varint] {
+        let bytes = to_vec(&scene, Endian::Big, lengths)?;
+        let decoded: Scene = from_slice(&bytes, Endian::Big, lengths)?;
+        assert_eq!(scene, decoded);
+    }
+
+    // Strings use the string modes of the reader and writer.
+    let entry = Entry {
+        name: "abc".to_string(),
+        id: 1,
+    };
+    let bytes = to_vec(&entry, Endian::Little, LengthEncoding::Varint)?;
+    let mut stream = SliceStream::new(&bytes);
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+    assert_eq!("abc", reader.read_7bit_encoded_len_string()?);
+    assert_eq!(1, reader.read_u64()?);
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_errors() -> Result<()> {
+    use binary_rw::{from_slice, to_vec, Deserializer, LengthEncoding};
+    use serde::Deserialize;
+    use serde_types::*;
+
+    let entries = vec![
+        Entry {
+            name: "first".to_string(),
+            id: 1,
+        },
+        Entry {
+            name: "second".to_string(),
+            id: 2,
+        },
+    ];
+    let bytes = to_vec(&entries, Endian::Big, LengthEncoding::Varint)?;
+    let truncated = &bytes[..bytes.len() - 2];
+    let error =
+        from_slice::<Vec<Entry>>(truncated, Endian::Big, LengthEncoding::Varint).unwrap_err();
+    assert!(matches!(
+        error,
+        BinaryError::Context { ref path, .. } if path == "[1].id"
+    ));
+
+    let mut stream = SliceStream::new(&bytes);
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    reader.set_limits(Limits {
+        max_elements: 1,
+        ..Default::default()
+    });
+    let mut deserializer = Deserializer::new(&mut reader, LengthEncoding::Varint);
+    assert!(matches!(
+        Vec::<Entry>::deserialize(&mut deserializer),
+        Err(BinaryError::ElementLimit { count: 2, limit: 1 })
+    ));
+
+    let bytes = to_vec(&9u32, Endian::Big, LengthEncoding::Fixed)?;
+    assert!(matches!(
+        from_slice::<Shape>(&bytes, Endian::Big, LengthEncoding::Fixed),
+        Err(BinaryError::Serde(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn trace_reads() -> Result<()> {
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_u32(0xCAFE)?;
+    writer.write_7bit_encoded_len_string("name")?;
+    writer.write_u16(3)?;
+    writer.write_bytes([0xAA; 20])?;
+
+    stream.seek(0)?;
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    assert!(reader.trace().is_none());
+    reader.start_trace();
+    reader.field("header", |r| {
+        r.field("magic", |r| r.read_u32())?;
+        r.read_7bit_encoded_len_string()
+    })?;
+    reader.read_u16()?;
+    reader.read_bytes(20)?;
+    assert!(reader.read_u8().is_err());
+    let trace = reader.take_trace().unwrap();
+    assert!(reader.trace().is_none());
+
+    assert_eq!(4, trace.entries().len());
+    assert_eq!(
+        TraceEntry {
+            method: "read_7bit_encoded_len_string",
+            offset: 4,
+            bytes: vec![4, b'n', b'a', b'm', b'e'],
+            value: "\"name\"".to_string(),
+            scope: "header".to_string(),
+        },
+        trace.entries()[1]
+    );
+    assert_eq!(
+        "header.magic:\n  \
+         00000000  00 00 ca fe                                      read_u32 51966\n\
+         header:\n  \
+         00000004  04 6e 61 6d 65                                   \
+         read_7bit_encoded_len_string \"name\"\n\
+         00000009  00 03                                            read_u16 3\n\
+         0000000b  aa aa aa aa aa aa aa aa aa aa aa aa aa aa aa aa  \
+         read_bytes [170, 170, 170, 170, 170, 170, 170, 170, 170, 170, \
+         170, 170, 170, 170, 170, 170, 170, 170, 170, 170]\n\
+         0000001b  aa aa aa aa\n",
+        trace.to_string()
+    );
+    Ok(())
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn cli_decode_and_layout() -> Result<()> {
+    use std::process::Command;
+
+    let name = "cli_decode_and_layout";
+    let mut stream = create_writer_stream(name);
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Little);
+    writer.write_u32(0xCAFE)?;
+    writer.write_7bit_encoded_len_string("name")?;
+    writer.write_u16(0x0102)?;
+    drop(stream);
+
+    let path = format!("{}.test", name);
+    let layout = "cli_decode_and_layout.layout.test";
+    std::fs::write(
+        layout,
+        "# header\nmagic: u32\nname: string7\nvalue: u16be\n",
+    )?;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_binary_rw"))
+        .args(["decode", &path, "u32be@0", "string7@0x4", "varint@4"])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(
+        "0x00000000  u32be@0  4274651136\n\
+         0x00000004  string7@0x4  \"name\"\n\
+         0x00000004  varint@4  4\n",
+        String::from_utf8(output.stdout)?
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_binary_rw"))
+        .args(["layout", &path, layout])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(
+        "0x00000000  magic  u32      51966\n\
+         0x00000004  name   string7  \"name\"\n\
+         0x00000009  value  u16be    513\n",
+        String::from_utf8(output.stdout)?
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_binary_rw"))
+        .args(["search", &path, "text:am"])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!("0x00000006\n", String::from_utf8(output.stdout)?);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_binary_rw"))
+        .args(["dump", &path, "2", "0x6"])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(
+        format!("00000002  {:47}  |...nam|\n", "00 00 04 6e 61 6d"),
+        String::from_utf8(output.stdout)?
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_binary_rw"))
+        .args(["decode", &path, "u64@8"])
+        .output()?;
+    assert!(!output.status.success());
+
+    cleanup(name);
+    cleanup("cli_decode_and_layout.layout");
+    Ok(())
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, Default, PartialEq, Encode, Decode)]
+#[binary_rw(magic = b"RIFF")]
+struct Chunk {
+    flags: u8,
+    len: u32,
+    #[binary_rw(pad_before = 3, count = self.len as usize)]
+    data: Vec<u16>,
+    #[binary_rw(if = self.flags & 1 != 0, assert(self.checksum != 0))]
+    checksum: u32,
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, Default, PartialEq, Encode, Decode)]
+struct Named(i16, #[binary_rw(magic = b"N")] String);
+
+#[cfg(feature = "derive")]
+#[derive(Debug, Default, PartialEq, Encode, Decode)]
+struct Wrapped<T>(#[binary_rw(pad_before = 100)] T);
+
+#[cfg(feature = "derive")]
+#[test]
+fn derive_layout() -> Result<()> {
+    let chunk = Chunk {
+        flags: 1,
+        len: 2,
+        data: vec![1, 2],
+        checksum: 7,
+    };
+    let mut stream = MemoryStream::new();
+    chunk.encode(&mut BinaryWriter::new(&mut stream, Endian::Big))?;
+    let mut expected = b"RIFF".to_vec();
+    expected.extend_from_slice(&[1, 0, 0, 0, 2, 0, 0, 0, 0, 1, 0, 2, 0, 0, 0, 7]);
+    assert_eq!(expected, stream.as_slice());
+
+    stream.seek(0)?;
+    let mut decoded = Chunk::default();
+    decoded.decode(&mut BinaryReader::new(&mut stream, Endian::Big))?;
+    assert_eq!(chunk, decoded);
+
+    // The checksum is only present when the flag is set
+    let empty = Chunk::default();
+    let mut stream = MemoryStream::new();
+    empty.encode(&mut BinaryWriter::new(&mut stream, Endian::Big))?;
+    assert_eq!(b"RIFF\0\0\0\0\0\0\0\0", stream.as_slice());
+    stream.seek(0)?;
+    decoded.decode(&mut BinaryReader::new(&mut stream, Endian::Big))?;
+    assert_eq!(empty, decoded);
+
+    let mismatched = Chunk {
+        len: 1,
+        ..Default::default()
+    };
+    let mut stream = MemoryStream::new();
+    assert!(matches!(
+        mismatched.encode(&mut BinaryWriter::new(&mut stream, Endian::Big)),
+        Err(BinaryError::CountMismatch {
+            expected: 1,
+            actual: 0
+        })
+    ));
+
+    let mut buffer = b"RIFF".to_vec();
+    buffer.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let mut stream = SliceStream::new(&buffer);
+    let result = Chunk::default().decode(&mut BinaryReader::new(&mut stream, Endian::Big));
+    match result {
+        Err(BinaryError::Context {
+            path,
+            offset,
+            source,
+        }) => {
+            assert_eq!("checksum", path);
+            assert_eq!(12, offset);
+            assert!(matches!(*source, BinaryError::AssertionFailed(_)));
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    buffer[3] = b'X';
+    let mut stream = SliceStream::new(&buffer);
+    let result = Chunk::default().decode(&mut BinaryReader::new(&mut stream, Endian::Big));
+    assert!(matches!(result, Err(BinaryError::UnexpectedBytes { .. })));
+
+    let named = Named(-2, "name".to_string());
+    let mut stream = MemoryStream::new();
+    named.encode(&mut BinaryWriter::new(&mut stream, Endian::Little))?;
+    assert_eq!(&[0xFE, 0xFF, b'N'], &stream.as_slice()[..3]);
+    stream.seek(0)?;
+    let mut decoded = Named::default();
+    decoded.decode(&mut BinaryReader::new(&mut stream, Endian::Little))?;
+    assert_eq!(named, decoded);
+
+    // Padding is skipped without counting against the allocation limits
+    let wrapped = Wrapped(7u32);
+    let mut stream = MemoryStream::new();
+    wrapped.encode(&mut BinaryWriter::new(&mut stream, Endian::Big))?;
+    assert_eq!(104, stream.len()?);
+    stream.seek(0)?;
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    reader.set_limits(Limits {
+        max_allocation: 2,
+        max_total_allocation: 2,
+        ..Default::default()
+    });
+    let mut decoded = Wrapped(0u32);
+    decoded.decode(&mut reader)?;
+    assert_eq!(wrapped, decoded);
+    Ok(())
+}
+
+#[test]
+fn expect_magic() -> Result<()> {
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Little);
+    assert_eq!(4, writer.write_magic_bytes(b"RIFF")?);
+    assert_eq!(4, writer.write_magic_u32(0x4556_4157)?);
+    writer.write_magic_u16(0xFEFF)?;
+    writer.write_magic_u64(1)?;
+
+    stream.seek(0)?;
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+    reader.expect_bytes(b"RIFF")?;
+    reader.expect_bytes(b"WAVE")?;
+    reader.expect_u16(0xFEFF)?;
+    reader.expect_u64(1)?;
+
+    reader.seek(4)?;
+    reader.swap_endianness();
+    reader.expect_u32(0x5741_5645)?;
+    reader.seek(4)?;
+    let error = reader.expect_u32(0x4556_4157).unwrap_err();
+    assert!(matches!(
+        error,
+        BinaryError::UnexpectedBytes { offset: 4, ref expected, ref actual }
+            if expected == b"EVAW" && actual == b"WAVE"
+    ));
+
+    reader.seek(0)?;
+    let error = reader.expect_bytes(b"RIFX").unwrap_err();
+    assert_eq!(
+        "expected [52, 49, 46, 58] at offset 0x0 but found [52, 49, 46, 46]",
+        error.to_string()
+    );
+
+    reader.seek(16)?;
+    assert!(matches!(
+        reader.expect_u32(0),
+        Err(BinaryError::ReadPastEof { offset: 16, .. })
+    ));
+    Ok(())
+}
+
+/// Version 1 stored the name, version 2 added a volume from 0 to 100
+/// and version 3 stores the volume from 0 to 1000 and a mute flag.
+#[derive(Debug, Default, PartialEq)]
+struct Settings {
+    name: String,
+    volume: u16,
+    muted: bool,
+}
+
+impl Encode for Settings {
+    fn encode(&self, writer: &mut BinaryWriter) -> binary_rw::Result<()> {
+        writer.write_string(&self.name)?;
+        writer.write_u16(self.volume)?;
+        writer.write_bool(self.muted)?;
+        Ok(())
+    }
+}
+
+impl Decode for Settings {
+    fn decode(&mut self, reader: &mut BinaryReader) -> binary_rw::Result<()> {
+        self.name = reader.read_string()?;
+        self.volume = reader.read_u16()?;
+        self.muted = reader.read_bool()?;
+        Ok(())
+    }
+}
+
+impl Versioned for Settings {
+    const VERSION: u32 = 3;
+
+    fn decode_version(&mut self, reader: &mut BinaryReader, version: u32) -> binary_rw::Result<()> {
+        match version {
+            1 => self.name = reader.read_string()?,
+            2 => {
+                self.name = reader.read_string()?;
+                self.volume = reader.read_u8()? as u16;
+            }
+            _ => return Err(BinaryError::UnsupportedVersion(version)),
+        }
+        Ok(())
+    }
+
+    fn upgrade(&mut self, version: u32) -> binary_rw::Result<()> {
+        match version {
+            1 => self.volume = 50,
+            2 => self.volume *= 10,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn versioned_encoding() -> Result<()> {
+    let current = Settings {
+        name: "speaker".to_string(),
+        volume: 300,
+        muted: true,
+    };
+
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_versioned(&current)?;
+    // Version 2
+    writer.write_7bit_encoded_u32(2)?;
+    writer.write_string("old")?;
+    writer.write_u8(20)?;
+    // Version 1
+    writer.write_7bit_encoded_u32(1)?;
+    writer.write_string("older")?;
+    // Unsupported versions
+    writer.write_7bit_encoded_u32(0)?;
+    writer.write_7bit_encoded_u32(4)?;
+
+    stream.seek(0)?;
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    assert_eq!(current, reader.read_versioned()?);
+    assert_eq!(
+        Settings {
+            name: "old".to_string(),
+            volume: 200,
+            muted: false,
+        },
+        reader.read_versioned()?
+    );
+    assert_eq!(
+        Settings {
+            name: "older".to_string(),
+            volume: 500,
+            muted: false,
+        },
+        reader.read_versioned()?
+    );
+    assert!(matches!(
+        reader.read_versioned::<Settings>(),
+        Err(BinaryError::UnsupportedVersion(0))
+    ));
+    assert!(matches!(
+        reader.read_versioned::<Settings>(),
+        Err(BinaryError::VersionTooNew {
+            version: 4,
+            supported: 3
+        })
+    ));
+    Ok(())
+}
+
+#[test]
+fn frame_round_trip() -> Result<()> {
+    let settings = Settings {
+        name: "speaker".to_string(),
+        volume: 300,
+        muted: true,
+    };
+    for prefix in [
+        LengthPrefix::U16,
+        LengthPrefix::U32,
+        LengthPrefix::U64,
+        LengthPrefix::Varint,
+    ] {
+        for big in [false, true] {
+            let endian = || if big { Endian::Big } else { Endian::Little };
+            let framing = Framing::new(prefix, 64);
+            let mut stream = MemoryStream::new();
+            let mut writer = BinaryWriter::new(&mut stream, endian());
+            writer.write_frame(&framing, &settings)?;
+            writer.write_frame_bytes(&framing, [])?;
+            writer.write_frame_bytes(&framing, b"raw")?;
+
+            stream.seek(0)?;
+            let mut reader = BinaryReader::new(&mut stream, endian());
+            assert_eq!(Some(&settings), reader.read_frame(&framing)?.as_ref());
+            assert_eq!(Some(vec![]), reader.read_frame_bytes(&framing)?);
+            assert_eq!(Some(b"raw".to_vec()), reader.read_frame_bytes(&framing)?);
+            assert_eq!(None, reader.read_frame_bytes(&framing)?);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn frame_size_and_truncation() -> Result<()> {
+    let framing = Framing::new(LengthPrefix::U32, 4);
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Little);
+    assert!(matches!(
+        writer.write_frame_bytes(&framing, b"too long"),
+        Err(BinaryError::FrameTooLarge { length: 8, max: 4 })
+    ));
+    assert_eq!(0, writer.tell()?);
+    writer.write_u32(8)?;
+    writer.write_bytes(b"too long")?;
+    writer.write_u32(4)?;
+    writer.write_bytes(b"ab")?;
+
+    stream.seek(0)?;
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+    assert!(matches!(
+        reader.read_frame_bytes(&framing),
+        Err(BinaryError::FrameTooLarge { length: 8, max: 4 })
+    ));
+    reader.seek(12)?;
+    assert!(matches!(
+        reader.read_frame_bytes(&framing),
+        Err(BinaryError::TruncatedFrame {
+            offset: 12,
+            received: 6
+        })
+    ));
+
+    // Truncated in the middle of the length prefix
+    let bytes = [2u8, 0];
+    let mut stream = SliceStream::new(&bytes);
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+    assert!(matches!(
+        reader.read_frame_bytes(&framing),
+        Err(BinaryError::TruncatedFrame {
+            offset: 0,
+            received: 2
+        })
+    ));
+    Ok(())
+}
+
+#[test]
+fn frame_over_socket() -> Result<()> {
+    use std::net::{Shutdown, TcpListener, TcpStream};
+
+    let framing = Framing::new(LengthPrefix::Varint, 1024);
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let address = listener.local_addr()?;
+    let sender = std::thread::spawn(move || -> binary_rw::Result<()> {
+        let socket = TcpStream::connect(address)?;
+        let mut stream = IoStream::new(socket);
+        let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+        for index in 0..3u16 {
+            let settings = Settings {
+                name: format!("client {}", index),
+                volume: index,
+                muted: index % 2 == 0,
+            };
+            writer.write_frame(&framing, &settings)?;
+        }
+        // Start a frame of 16 bytes and hang up part way through
+        writer.write_7bit_encoded_usize(16)?;
+        writer.write_bytes(b"partial")?;
+        stream.get_ref().shutdown(Shutdown::Write)?;
+        Ok(())
+    });
+
+    let (socket, _) = listener.accept()?;
+    let mut stream = IoStream::new(socket);
+    let mut reader = BinaryReader::new(&mut stream, Endian::Big);
+    for index in 0..3u16 {
+        let settings: Settings = reader.read_frame(&framing)?.unwrap();
+        assert_eq!(format!("client {}", index), settings.name);
+        assert_eq!(index, settings.volume);
+    }
+    let offset = reader.tell()?;
+    assert!(matches!(
+        reader.read_frame_bytes(&framing),
+        Err(BinaryError::TruncatedFrame { offset: o, received: 8 }) if o == offset
+    ));
+    assert_eq!(None, reader.read_frame_bytes(&framing)?);
+    sender.join().unwrap()?;
+    Ok(())
+}
+
+#[test]
+fn frame_short_writes() -> Result<()> {
+    /// Writer that accepts a single byte per call.
+    struct Trickle(Vec<u8>);
+
+    impl std::io::Write for Trickle {
+        fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+            self.0.extend_from_slice(&bytes[..bytes.len().min(1)]);
+            Ok(bytes.len().min(1))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let framing = Framing::new(LengthPrefix::U32, 16);
+    let mut stream = IoStream::new(Trickle(Vec::new()));
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    assert_eq!(9, writer.write_frame_bytes(&framing, b"hello")?);
+    assert_eq!(9, writer.tell()?);
+    assert_eq!(b"\0\0\0\x05hello", stream.get_ref().0.as_slice());
+    Ok(())
+}
+
+#[test]
+fn record_log() -> Result<()> {
+    let name = "record_log";
+    let path = format!("{}.test", name);
+    drop(create_writer_stream(name));
+
+    let mut log = RecordLog::open(&path, Endian::Little)?;
+    log.set_sync_policy(SyncPolicy::Always);
+    for index in 0..3u16 {
+        let settings = Settings {
+            name: format!("record {}", index),
+            volume: index,
+            muted: false,
+        };
+        log.append(&settings)?;
+    }
+    let end = log.end()?;
+    drop(log);
+
+    // Simulate a crash part way through appending a record
+    let mut stream = FileStream::write(&path)?;
+    stream.seek(end)?;
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Little);
+    writer.write_u32(100)?;
+    writer.write_u32(0)?;
+    writer.write_bytes(b"torn")?;
+    drop(stream);
+
+    let mut log = RecordLog::open(&path, Endian::Little)?;
+    assert_eq!(12, log.truncated());
+    assert_eq!(end, log.end()?);
+    let volumes = log
+        .iter::<Settings>()
+        .map(|settings| Ok(settings?.volume))
+        .collect::<binary_rw::Result<Vec<_>>>()?;
+    assert_eq!(vec![0, 1, 2], volumes);
+
+    // Resume after the first record
+    let mut records = log.iter::<Settings>();
+    assert_eq!("record 0", records.next().unwrap()?.name);
+    let resume = records.offset();
+    let offset = log.append(&Settings::default())?;
+    assert_eq!(end, offset);
+    log.sync()?;
+    let names = log
+        .iter_from::<Settings>(resume)
+        .map(|settings| Ok(settings?.name))
+        .collect::<binary_rw::Result<Vec<_>>>()?;
+    assert_eq!(vec!["record 1", "record 2", ""], names);
+    drop(log);
+
+    // Corrupt the payload of the first record
+    let mut stream = FileStream::write(&path)?;
+    stream.seek(12)?;
+    BinaryWriter::new(&mut stream, Endian::Little).write_u8(0xFF)?;
+    drop(stream);
+    assert!(matches!(
+        RecordLog::open(&path, Endian::Little),
+        Err(BinaryError::CorruptRecord { offset: 0 })
+    ));
+
+    cleanup(name);
+    Ok(())
+}
+
+#[test]
+fn record_log_corrupt_length() -> Result<()> {
+    let name = "record_log_corrupt_length";
+    let path = format!("{}.test", name);
+    drop(create_writer_stream(name));
+
+    let mut log = RecordLog::open(&path, Endian::Little)?;
+    log.append_bytes(b"first")?;
+    let second = log.append_bytes(b"second")?;
+    log.append_bytes(b"third")?;
+    let end = log.end()?;
+    drop(log);
+
+    // A length pointing past the end of the file is not a torn record
+    let mut stream = FileStream::write(&path)?;
+    stream.seek(second)?;
+    BinaryWriter::new(&mut stream, Endian::Little).write_u32(1000)?;
+    drop(stream);
+    assert!(matches!(
+        RecordLog::open(&path, Endian::Little),
+        Err(BinaryError::CorruptRecord { offset }) if offset == second
+    ));
+    assert_eq!(end, std::fs::metadata(&path)?.len() as usize);
+
+    cleanup(name);
+    Ok(())
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Encode for Point {
+    fn encode(&self, writer: &mut BinaryWriter) -> binary_rw::Result<()> {
+        writer.write_i32(self.x)?;
+        writer.write_i32(self.y)?;
+        Ok(())
+    }
+}
+
+impl Decode for Point {
+    fn decode(&mut self, reader: &mut BinaryReader) -> binary_rw::Result<()> {
+        self.x = reader.read_i32()?;
+        self.y = reader.read_i32()?;
+        Ok(())
+    }
+}
+
+impl FixedSize for Point {
+    const SIZE: usize = 8;
+}
+
+#[test]
+fn record_file() -> Result<()> {
+    let name = "record_file";
+    let path = format!("{}.test", name);
+
+    let mut file = RecordFile::<Point>::create(&path, Endian::Big, b"PNTS")?;
+    assert!(file.is_empty());
+    for index in 0..4 {
+        let point = Point {
+            x: index,
+            y: -index,
+        };
+        assert_eq!(index as usize, file.push(&point)?);
+    }
+    file.set(2, &Point { x: 20, y: 40 })?;
+    assert!(matches!(
+        file.set(4, &Point::default()),
+        Err(BinaryError::IndexOutOfBounds { index: 4, len: 4 })
+    ));
+    file.sync()?;
+    drop(file);
+
+    let mut file = RecordFile::<Point>::open(&path, Endian::Big, 4)?;
+    assert_eq!(4, file.len());
+    assert_eq!(b"PNTS".to_vec(), file.header()?);
+    assert_eq!(Point { x: 3, y: -3 }, file.get(3)?);
+    let points = file.iter().collect::<binary_rw::Result<Vec<_>>>()?;
+    assert_eq!(
+        vec![
+            Point { x: 0, y: 0 },
+            Point { x: 1, y: -1 },
+            Point { x: 20, y: 40 },
+            Point { x: 3, y: -3 },
+        ],
+        points
+    );
+    assert!(matches!(
+        file.get(4),
+        Err(BinaryError::IndexOutOfBounds { index: 4, len: 4 })
+    ));
+
+    // A partial record makes the file invalid
+    let mut stream = file.into_inner();
+    stream.seek(stream.len()?)?;
+    BinaryWriter::new(&mut stream, Endian::Big).write_bytes([0, 0, 0])?;
+    drop(stream);
+    assert!(matches!(
+        RecordFile::<Point>::open(&path, Endian::Big, 4),
+        Err(BinaryError::InvalidRecordFile {
+            length: 39,
+            header: 4,
+            record: 8
+        })
+    ));
+
+    cleanup(name);
+    Ok(())
+}
+
+#[test]
+fn atomic_file() -> Result<()> {
+    let name = "atomic_file";
+    let path = format!("{}.test", name);
+    let mut stream = create_writer_stream(name);
+    BinaryWriter::new(&mut stream, Endian::Little).write_string("old")?;
+    drop(stream);
+
+    // Dropped without committing
+    let mut file = AtomicFile::create(&path)?;
+    let temp_path = file.temp_path().to_path_buf();
+    assert!(temp_path.exists());
+    BinaryWriter::new(&mut file, Endian::Little).write_string("discarded")?;
+    drop(file);
+    assert!(!temp_path.exists());
+    let mut stream = create_reader_stream(name);
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+    assert_eq!("old", reader.read_string()?);
+    drop(stream);
+
+    let mut file = AtomicFile::create(&path)?.buffered(64);
+    let temp_path = file.temp_path().to_path_buf();
+    let mut writer = BinaryWriter::new(&mut file, Endian::Little);
+    writer.write_string("new")?;
+    writer.write_u32(42)?;
+    file.commit()?;
+    assert!(!temp_path.exists());
+
+    let mut stream = create_reader_stream(name);
+    let mut reader = BinaryReader::new(&mut stream, Endian::Little);
+    assert_eq!("new", reader.read_string()?);
+    assert_eq!(42, reader.read_u32()?);
+    drop(stream);
+
+    cleanup(name);
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn atomic_file_permissions() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let name = "atomic_file_permissions";
+    let path = format!("{}.test", name);
+    drop(create_writer_stream(name));
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+
+    let mut file = AtomicFile::create(&path)?;
+    BinaryWriter::new(&mut file, Endian::Little).write_u32(42)?;
+    file.commit()?;
+    let mode = std::fs::metadata(&path)?.permissions().mode();
+    assert_eq!(0o600, mode & 0o777);
+
+    cleanup(name);
+    Ok(())
+}
+
+#[test]
+fn endian_overrides() -> Result<()> {
+    let mut stream = MemoryStream::new();
+    let mut writer = BinaryWriter::new(&mut stream, Endian::Big);
+    writer.write_u32_le(1u32)?;
+    writer.write_u16(2u16)?;
+    writer.write_i16_le(-2i16)?;
+    writer.set_endian(Endian::Little);
+    assert_eq!(Endian::Little, writer.endian());
+    writer.write_u16_be(3u16)?;
+    writer.write_u16(4u16)?;
+    writer.write_f64_be(1.5f64)?;
+    writer.write_char_be('a')?;
+
+    let bytes: Vec<u8> = stream.into();
+    assert_eq!(
+        vec![1, 0, 0, 0, 0, 2, 0xFE, 0xFF, 0, 3, 4, 0],
+        bytes[..12].to_vec()
+    );
+
+    let mut stream = SliceStream::new(&bytes);
+    let mut reader = BinaryReader::new(&mut stream, Endian::NETWORK);
+    assert_eq!(1, reader.read_u32_le()?);
+    assert_eq!(2, reader.read_u16()?);
+    assert_eq!(-2, reader.read_i16_le()?);
+    assert_eq!(Endian::Big, reader.endian());
+    reader.set_endian(Endian::Little);
+    assert_eq!(3, reader.read_u16_be()?);
+    assert_eq!(4, reader.read_u16()?);
+    assert_eq!(1.5, reader.read_f64_be()?);
+    assert_eq!('a', reader.read_char_be()?);
+
+    let native = if cfg!(target_endian = "little") {
+        Endian::Little
+    } else {
+        Endian::Big
+    };
+    assert_eq!(native, Endian::Native.resolve());
+    assert!(Endian::Native.is_native());
+    assert_eq!(native.swap(), Endian::Native.swap());
+    let mut stream = MemoryStream::new();
+    BinaryWriter::new(&mut stream, Endian::Native).write_u32(0x0102_0304u32)?;
+    let bytes: Vec<u8> = stream.into();
+    assert_eq!(0x0102_0304u32.to_ne_bytes().to_vec(), bytes);
+    Ok(())
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Tagged {
+    magic: U32Le,
+    delta: I16Be,
+    scale: F64Le,
+    count: Be<u64>,
+    flags: Le<u8>,
+}
+
+impl Encode for Tagged {
+    fn encode(&self, writer: &mut BinaryWriter) -> binary_rw::Result<()> {
+        self.magic.encode(writer)?;
+        self.delta.encode(writer)?;
+        self.scale.encode(writer)?;
+        self.count.encode(writer)?;
+        self.flags.encode(writer)?;
+        Ok(())
+    }
+}
+
+impl Decode for Tagged {
+    fn decode(&mut self, reader: &mut BinaryReader) -> binary_rw::Result<()> {
+        self.magic.decode(reader)?;
+        self.delta.decode(reader)?;
+        self.scale.decode(reader)?;
+        self.count.decode(reader)?;
+        self.flags.decode(reader)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn endian_tagged_values() -> Result<()> {
+    let mut tagged = Tagged {
+        magic: U32Le::new(0x0102_0304),
+        delta: (-2i16).into(),
+        scale: F64Le::new(0.5),
+        count: Be::new(7),
+        flags: Le::new(0x80),
+    };
+    tagged.count.set(9);
+    assert_eq!(9, tagged.count.get());
+    assert_eq!(-2, i16::from(tagged.delta));
+
+    let mut expected = vec![4, 3, 2, 1, 0xFF, 0xFE];
+    expected.extend_from_slice(&0.5f64.to_le_bytes());
+    expected.extend_from_slice(&9u64.to_be_bytes());
+    expected.push(0x80);
+
+    // The byte order does not depend on the endianness of the stream
+    for endian in [Endian::Big, Endian::Little, Endian::Native] {
+        let mut stream = MemoryStream::new();
+        let mut writer = BinaryWriter::new(&mut stream, endian);
+        tagged.encode(&mut writer)?;
+        assert_eq!(endian, writer.endian());
+        let bytes: Vec<u8> = stream.into();
+        assert_eq!(expected, bytes);
+
+        let mut stream = SliceStream::new(&bytes);
+        let mut reader = BinaryReader::new(&mut stream, endian);
+        let mut decoded = Tagged::default();
+        decoded.decode(&mut reader)?;
+        assert_eq!(tagged, decoded);
+        assert_eq!(endian, reader.endian());
+    }
+    Ok(())
+}